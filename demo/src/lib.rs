@@ -76,9 +76,9 @@ impl eframe::App for MyApp {
         }
 
         // Draw the map widget.
-        let response = map.show(ui, |ui, _, projector, map_memory| {
+        let response = map.show(ui, |ui, _, projector, _| {
             for layer in &self.geojson_layers {
-                layer.render(ui, projector, map_memory.zoom().round() as u8);
+                layer.render(ui, projector, projector.zoom_level());
             }
 
             // You can add any additional contents to the map's UI here.
@@ -94,7 +94,8 @@ impl ClickWatcher {
 
 impl Plugin for &mut ClickWatcher {
     fn run(self: Box<Self>, ui: &mut Ui, response: &Response, projector: &ScreenProjector) {
-        if !response.changed() && response.clicked_by(egui::PointerButton::Primary) {
+        let clicked = !response.changed() && response.clicked_by(egui::PointerButton::Primary);
+        if clicked || response.long_touched() {
             self.clicked_at = response
                 .interact_pointer_pos()
                 .map(|p| projector.unproject(p));
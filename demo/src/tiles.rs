@@ -5,10 +5,13 @@ use egui::Context;
 use walkers::PmTiles;
 #[cfg(feature = "mvt")]
 use walkers::Style;
-use walkers::{HttpOptions, HttpTiles, MercatorProjection, Tiles};
+use walkers::{DebugTiles, HttpOptions, HttpTiles, MercatorProjection, Tiles};
 
+// There are just a few of these, so boxing would not save anything.
+#[allow(clippy::large_enum_variant)]
 pub(crate) enum TilesKind {
     Http(HttpTiles<MercatorProjection>),
+    Debug(DebugTiles),
     #[cfg(feature = "pmtiles")]
     PmTiles(PmTiles<MercatorProjection>),
 }
@@ -17,6 +20,7 @@ impl AsMut<dyn Tiles<Projection = MercatorProjection>> for TilesKind {
     fn as_mut(&mut self) -> &mut (dyn Tiles<Projection = MercatorProjection> + 'static) {
         match self {
             TilesKind::Http(tiles) => tiles,
+            TilesKind::Debug(tiles) => tiles,
             #[cfg(feature = "pmtiles")]
             TilesKind::PmTiles(tiles) => tiles,
         }
@@ -27,6 +31,7 @@ impl AsRef<dyn Tiles<Projection = MercatorProjection>> for TilesKind {
     fn as_ref(&self) -> &(dyn Tiles<Projection = MercatorProjection> + 'static) {
         match self {
             TilesKind::Http(tiles) => tiles,
+            TilesKind::Debug(tiles) => tiles,
             #[cfg(feature = "pmtiles")]
             TilesKind::PmTiles(tiles) => tiles,
         }
@@ -86,6 +91,27 @@ pub(crate) fn providers(egui_ctx: Context) -> Providers {
         ))],
     );
 
+    providers.available.insert(
+        "CartoDarkMatter".to_string(),
+        vec![TilesKind::Http(HttpTiles::with_options(
+            walkers::sources::Carto {
+                style: walkers::sources::CartoStyle::DarkMatter,
+                high_resolution: false,
+            },
+            http_options(),
+            egui_ctx.to_owned(),
+        ))],
+    );
+
+    providers.available.insert(
+        "EsriWorldImagery".to_string(),
+        vec![TilesKind::Http(HttpTiles::with_options(
+            walkers::sources::Esri(walkers::sources::EsriMap::WorldImagery),
+            http_options(),
+            egui_ctx.to_owned(),
+        ))],
+    );
+
     providers.available.insert(
         "Geoportal".to_string(),
         vec![TilesKind::Http(HttpTiles::with_options(
@@ -120,6 +146,11 @@ pub(crate) fn providers(egui_ctx: Context) -> Providers {
         ))],
     );
 
+    providers.available.insert(
+        "Debug".to_string(),
+        vec![TilesKind::Debug(DebugTiles::new(egui_ctx.to_owned()))],
+    );
+
     #[cfg(feature = "pmtiles")]
     {
         let pmtiles = find_pmtiles_files();
@@ -209,6 +240,22 @@ pub(crate) fn providers(egui_ctx: Context) -> Providers {
         );
     }
 
+    // Same goes for Thunderforest.
+    if let Some(api_key) = std::option_env!("THUNDERFOREST_API_KEY") {
+        providers.available.insert(
+            "ThunderforestOutdoors".to_string(),
+            vec![TilesKind::Http(HttpTiles::with_options(
+                walkers::sources::Thunderforest {
+                    style: walkers::sources::ThunderforestStyle::Outdoors,
+                    api_key: api_key.to_string(),
+                    high_resolution: false,
+                },
+                http_options(),
+                egui_ctx.to_owned(),
+            ))],
+        );
+    }
+
     providers
 }
 
@@ -148,6 +148,26 @@ impl AnticipatedRequest {
         self.payload_tx.send(response).unwrap();
     }
 
+    /// Similar to [AnticipatedRequest::respond_with_status], but with additional headers.
+    pub async fn respond_with_status_and_headers(
+        self,
+        status: hyper::StatusCode,
+        headers: &[(&str, &str)],
+    ) {
+        log::info!(
+            "Saving response (with status: {} and headers: {:?}) for '{}'.",
+            status,
+            headers,
+            self.url
+        );
+        let mut response = hyper::Response::builder().status(status);
+        for (name, value) in headers {
+            response = response.header(*name, *value);
+        }
+        let response = response.body(Full::new(Bytes::default())).unwrap();
+        self.payload_tx.send(response).unwrap();
+    }
+
     /// Expect the request to come, but do not respond to it yet.
     pub async fn expect(&mut self) -> HyperRequest {
         log::info!("Expecting '{}'.", self.url);
@@ -1,9 +1,14 @@
 use crate::{Position, position::AdjustedPosition, projector::Projection};
 use egui::{DragPanButtons, PointerButton, Response, Vec2};
 
-/// Time constant of inertia stopping filter
+/// Time constant of inertia stopping filter, in seconds.
 const INERTIA_TAU: f32 = 0.2f32;
 
+/// Speed, in pixels per second, below which inertia stops the map. At that speed, the map moves
+/// by a tenth of a pixel per frame and there is just over a pixel left to travel, which is
+/// covered at once, so that the map goes idle and stops requesting repaints.
+const INERTIA_MIN_SPEED: f32 = 6.0;
+
 /// Position of the map's center. Initially, the map follows `my_position` argument which typically
 /// is meant to be fed by a GPS sensor or other geo-localization method. If user drags the map,
 /// it becomes "detached" and stays this way until [`MapMemory::center_mode`] is changed back to
@@ -30,12 +35,27 @@ pub(crate) enum Center {
     Inertia {
         position: AdjustedPosition,
         direction: Vec2,
+        /// Speed in pixels per second.
         amount: f32,
     },
 
     /// Map is being pulled back to the `my_position`. This happens when the user releases the
     /// dragging gesture, but the map is too close to the `my_position`.
     PulledToMyPosition(AdjustedPosition),
+
+    /// Map is gliding back to `my_position`, see [`crate::MapMemory::recenter_animated`].
+    Recentering {
+        /// Where the map started from, as an offset from `my_position`.
+        from: AdjustedPosition,
+        /// Duration of the animation, in seconds.
+        duration: f32,
+        elapsed: f32,
+    },
+}
+
+/// Easing which slows down towards the end of an animation. `t` goes from 0 to 1.
+pub(crate) fn ease_out(t: f64) -> f64 {
+    1. - (1. - t).powi(3)
 }
 
 impl Center {
@@ -45,12 +65,14 @@ impl Center {
         my_position: Position,
         pull_to_my_position_threshold: f32,
         drag_pan_buttons: DragPanButtons,
+        inertia: bool,
+        delta_time: f32,
     ) -> bool {
         if dragged_by(response, drag_pan_buttons) {
             self.dragged_by(my_position, response);
             true
         } else if response.drag_stopped() {
-            self.drag_stopped(pull_to_my_position_threshold);
+            self.drag_stopped(pull_to_my_position_threshold, inertia, delta_time);
             true
         } else {
             false
@@ -74,21 +96,29 @@ impl Center {
         };
     }
 
-    fn drag_stopped(&mut self, pull_to_my_position_threshold: f32) {
+    /// `delta_time` is the duration of the last frame, used to turn the last drag delta into
+    /// the speed of inertia. Without `inertia`, the map stops right where it was released.
+    fn drag_stopped(&mut self, pull_to_my_position_threshold: f32, inertia: bool, delta_time: f32) {
         if let Center::Moving {
             position,
             direction,
             from_detached,
         } = &self
         {
-            if *from_detached || position.offset_length() > pull_to_my_position_threshold {
+            if !*from_detached && position.offset_length() <= pull_to_my_position_threshold {
+                *self = Center::PulledToMyPosition(position.to_owned());
+            } else if !inertia {
+                *self = Center::Exact(position.to_owned());
+            } else {
                 *self = Center::Inertia {
                     position: position.clone(),
                     direction: direction.normalized(),
-                    amount: direction.length(),
+                    amount: if delta_time > 0. {
+                        direction.length() / delta_time
+                    } else {
+                        0.
+                    },
                 };
-            } else {
-                *self = Center::PulledToMyPosition(position.to_owned());
             }
         }
     }
@@ -112,16 +142,23 @@ impl Center {
                 direction,
                 amount,
             } => {
-                *self = if amount < &mut 0.1 {
-                    Center::Exact(position.to_owned())
+                *self = if *amount < INERTIA_MIN_SPEED {
+                    // Cover the remaining distance, so that it is exactly what was requested.
+                    Center::Exact(
+                        position
+                            .clone()
+                            .shift(*direction * *amount * INERTIA_TAU, zoom),
+                    )
                 } else {
-                    // Exponentially drive the `amount` value towards zero
-                    let lp_factor = INERTIA_TAU / (delta_time + INERTIA_TAU);
+                    // Speed decays exponentially. Distance is the integral of the speed over the
+                    // frame, so the total distance travelled does not depend on the frame rate.
+                    let decay = (-delta_time / INERTIA_TAU).exp();
+                    let distance = *amount * INERTIA_TAU * (1. - decay);
 
                     Center::Inertia {
-                        position: position.clone().shift(*direction * *amount, zoom),
+                        position: position.clone().shift(*direction * distance, zoom),
                         direction: *direction,
-                        amount: *amount * lp_factor,
+                        amount: *amount * decay,
                     }
                 };
                 true
@@ -135,6 +172,23 @@ impl Center {
                 };
                 true
             }
+            Center::Recentering {
+                from,
+                duration,
+                elapsed,
+            } => {
+                let elapsed = elapsed + delta_time;
+                *self = if elapsed >= *duration {
+                    Center::MyPosition
+                } else {
+                    Center::Recentering {
+                        from: from.clone(),
+                        duration: *duration,
+                        elapsed,
+                    }
+                };
+                true
+            }
             _ => false,
         }
     }
@@ -146,7 +200,15 @@ impl Center {
     }
 
     pub(crate) fn animating(&self) -> bool {
-        matches!(self, Center::Inertia { .. } | Center::PulledToMyPosition(_))
+        matches!(
+            self,
+            Center::Inertia { .. } | Center::PulledToMyPosition(_) | Center::Recentering { .. }
+        )
+    }
+
+    /// Whether the map is being dragged, or moves on its own after being released.
+    pub(crate) fn moving(&self) -> bool {
+        matches!(self, Center::Moving { .. }) || self.animating()
     }
 
     fn adjusted_position(&self) -> Option<AdjustedPosition> {
@@ -156,6 +218,18 @@ impl Center {
             | Center::PulledToMyPosition(position)
             | Center::Moving { position, .. }
             | Center::Inertia { position, .. } => Some(position.to_owned()),
+            Center::Recentering {
+                from,
+                duration,
+                elapsed,
+            } => {
+                let t = if *duration > 0. {
+                    (elapsed / duration).clamp(0., 1.) as f64
+                } else {
+                    1.
+                };
+                Some(from.clone().scaled_offset(1. - ease_out(t)))
+            }
         }
     }
 
@@ -168,6 +242,24 @@ impl Center {
         self.detached(projection).unwrap_or(my_position)
     }
 
+    /// Pan by given number of pixels, the same way dragging does, detaching the map. If `smooth`,
+    /// the map glides there and slows down like after a drag. `None` if the map follows
+    /// `my_position`, as there is no position to pan from.
+    pub(crate) fn panned(&self, offset: Vec2, zoom: f64, smooth: bool) -> Option<Self> {
+        let position = self.adjusted_position()?;
+
+        Some(if smooth && offset != Vec2::ZERO {
+            Center::Inertia {
+                position,
+                direction: offset.normalized(),
+                // Total distance travelled by inertia is its initial speed times tau.
+                amount: offset.length() / INERTIA_TAU,
+            }
+        } else {
+            Center::Exact(position.shift(offset, zoom))
+        })
+    }
+
     /// Shift position by given number of pixels, if detached.
     pub(crate) fn shift(self, offset: Vec2, zoom: f64) -> Self {
         match self {
@@ -194,6 +286,15 @@ impl Center {
                 direction,
                 amount,
             },
+            Center::Recentering {
+                from,
+                duration,
+                elapsed,
+            } => Center::Recentering {
+                from: from.shift(offset, zoom),
+                duration,
+                elapsed,
+            },
         }
     }
 }
@@ -208,3 +309,90 @@ fn dragged_by(response: &Response, buttons: DragPanButtons) -> bool {
         _ => false,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lon_lat, projector::MercatorProjection};
+
+    /// Let the map glide until it stops, returning the distance travelled in pixels.
+    fn inertia_travel(fps: f32) -> f32 {
+        let delta_time = 1. / fps;
+        let zoom = 10.;
+        let start = lon_lat(17., 51.);
+
+        // Same drag speed of 600 px/s, so the per-frame delta depends on the frame rate.
+        let mut center = Center::Moving {
+            position: AdjustedPosition::new(start),
+            direction: Vec2::new(600. * delta_time, 0.),
+            from_detached: true,
+        };
+        center.drag_stopped(0., true, delta_time);
+
+        while center.animating() {
+            center.update_movement(delta_time, zoom);
+        }
+
+        let projection = MercatorProjection;
+        let end = center.position(start, &projection);
+        (projection.position_to_pixels(end, zoom) - projection.position_to_pixels(start, zoom))
+            .x()
+            .abs() as f32
+    }
+
+    #[test]
+    fn map_stops_right_away_without_inertia() {
+        let position = AdjustedPosition::new(lon_lat(17., 51.));
+        let mut center = Center::Moving {
+            position: position.clone(),
+            direction: Vec2::new(10., 0.),
+            from_detached: true,
+        };
+        center.drag_stopped(0., false, 1. / 60.);
+
+        assert!(!center.animating());
+        assert_eq!(center, Center::Exact(position));
+    }
+
+    #[test]
+    fn inertia_stops_once_movement_is_imperceptible() {
+        let delta_time = 1. / 60.;
+        let zoom = 10.;
+        let start = lon_lat(17., 51.);
+        let projection = MercatorProjection;
+        let x = |center: &Center| {
+            projection
+                .position_to_pixels(center.position(start, &projection), zoom)
+                .x() as f32
+        };
+
+        let mut center = Center::Moving {
+            position: AdjustedPosition::new(start),
+            direction: Vec2::new(600. * delta_time, 0.),
+            from_detached: true,
+        };
+        center.drag_stopped(0., true, delta_time);
+
+        let mut frames = 0;
+        let mut last_step = 0.;
+        while center.animating() {
+            let before = x(&center);
+            assert!(center.update_movement(delta_time, zoom));
+            last_step = x(&center) - before;
+            frames += 1;
+        }
+
+        // Less than a second, rather than until the speed decays to zero.
+        assert!(frames < 60, "{frames}");
+        assert!(last_step < 1.5, "{last_step}");
+        assert!(!center.update_movement(delta_time, zoom));
+    }
+
+    #[test]
+    fn inertia_travel_does_not_depend_on_frame_rate() {
+        for fps in [30., 60., 120., 144.] {
+            let travel = inertia_travel(fps);
+            assert!((travel - 120.).abs() < 0.01, "{fps} fps: {travel}");
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use crate::{MercatorProjection, Tile, TileId, TilePiece, Tiles, sources::Attribution};
+use egui::{Color32, ColorImage, Context, Rect, TextureOptions, pos2};
+use lru::LruCache;
+
+/// Generates tiles offline, as a checkerboard of solid colors labeled with their zoom/x/y. Useful
+/// for examples, tests and for checking projection without depending on a tile server.
+pub struct DebugTiles {
+    egui_ctx: Context,
+    tile_size: u32,
+    cache: LruCache<TileId, Tile>,
+}
+
+impl DebugTiles {
+    pub fn new(egui_ctx: Context) -> Self {
+        // Just arbitrary value which seemed right.
+        #[allow(clippy::unwrap_used)]
+        let cache_size = std::num::NonZeroUsize::new(256).unwrap();
+
+        Self {
+            egui_ctx,
+            tile_size: 256,
+            cache: LruCache::new(cache_size),
+        }
+    }
+
+    /// Size of generated tiles. Default is 256.
+    pub fn with_tile_size(mut self, tile_size: u32) -> Self {
+        self.tile_size = tile_size;
+        self.cache.clear();
+        self
+    }
+}
+
+impl Tiles for DebugTiles {
+    type Projection = MercatorProjection;
+
+    fn at(&mut self, tile_id: TileId) -> Option<TilePiece> {
+        if !tile_id.valid() {
+            return None;
+        }
+
+        let tile = self
+            .cache
+            .get_or_insert(tile_id, || {
+                let image = debug_image(tile_id, self.tile_size as usize);
+                Tile::Raster(self.egui_ctx.load_texture(
+                    format!("debug tile {tile_id:?}"),
+                    image,
+                    TextureOptions::LINEAR,
+                ))
+            })
+            .clone();
+
+        Some(TilePiece::new(
+            tile,
+            Rect::from_min_max(pos2(0., 0.), pos2(1., 1.)),
+        ))
+    }
+
+    fn attribution(&self) -> Attribution {
+        Attribution {
+            text: "Debug tiles",
+            url: "",
+            logo_light: None,
+            logo_dark: None,
+        }
+    }
+
+    fn tile_size(&self) -> u32 {
+        self.tile_size
+    }
+}
+
+const LIGHT: Color32 = Color32::from_gray(230);
+const DARK: Color32 = Color32::from_gray(200);
+const INK: Color32 = Color32::from_gray(60);
+
+/// Solid color image, alternating between neighbouring tiles, with a border and the tile's
+/// zoom/x/y written in its top left corner.
+fn debug_image(tile_id: TileId, size: usize) -> ColorImage {
+    let background = if (tile_id.x + tile_id.y).is_multiple_of(2) {
+        LIGHT
+    } else {
+        DARK
+    };
+    let mut image = ColorImage::filled([size, size], background);
+
+    for i in 0..size {
+        for (x, y) in [(i, 0), (i, size - 1), (0, i), (size - 1, i)] {
+            image[(x, y)] = INK;
+        }
+    }
+
+    let label = format!("{}/{}/{}", tile_id.zoom, tile_id.x, tile_id.y);
+    let margin = 8;
+    let advance = GLYPH_WIDTH + 1;
+    let scale = ((size.saturating_sub(2 * margin)) / (label.len() * advance)).clamp(1, 4);
+
+    for (i, c) in label.chars().enumerate() {
+        let left = margin + i * advance * scale;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                    continue;
+                }
+
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let x = left + column * scale + dx;
+                        let y = margin + row * scale + dy;
+                        if x < size && y < size {
+                            image[(x, y)] = INK;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    image
+}
+
+const GLYPH_WIDTH: usize = 3;
+
+/// Rows of a tiny bitmap font, just enough to write tile ids without any font rasterizer.
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        _ => [0; 5],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbouring_tiles_have_different_colors() {
+        let image = debug_image(
+            TileId {
+                x: 3,
+                y: 5,
+                zoom: 4,
+            },
+            256,
+        );
+        let neighbour = debug_image(
+            TileId {
+                x: 4,
+                y: 5,
+                zoom: 4,
+            },
+            256,
+        );
+
+        assert_eq!(image[(128, 128)], LIGHT);
+        assert_eq!(neighbour[(128, 128)], DARK);
+        assert_eq!(image[(0, 128)], INK);
+        assert_eq!(image[(255, 255)], INK);
+    }
+
+    #[test]
+    fn label_fits_the_tile() {
+        let id = TileId {
+            x: 1_048_575,
+            y: 1_048_575,
+            zoom: 20,
+        };
+
+        for size in [128, 256, 512] {
+            let image = debug_image(id, size);
+            let labeled = (1..size - 1).any(|x| image[(x, 8)] == INK);
+            assert!(labeled, "{size}");
+
+            // Nothing is written in the right margin.
+            assert!(
+                (size - 8..size - 1).all(|x| image[(x, 10)] != INK),
+                "{size}"
+            );
+        }
+    }
+
+    #[test]
+    fn tiles_are_generated_once() {
+        let ctx = Context::default();
+        let mut tiles = DebugTiles::new(ctx.clone());
+        let id = TileId {
+            x: 1,
+            y: 1,
+            zoom: 1,
+        };
+
+        let first = tiles.at(id).expect("debug tile");
+        let second = tiles.at(id).expect("debug tile");
+        match (first.tile, second.tile) {
+            (Tile::Raster(first), Tile::Raster(second)) => assert_eq!(first.id(), second.id()),
+            _ => panic!("expected raster tiles"),
+        }
+
+        assert!(
+            tiles
+                .at(TileId {
+                    x: 2,
+                    y: 0,
+                    zoom: 1
+                })
+                .is_none()
+        );
+    }
+}
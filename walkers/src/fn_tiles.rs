@@ -0,0 +1,180 @@
+use crate::{
+    MercatorProjection, Tile, TileId, TilePiece, Tiles, projector::Projection, sources::Attribution,
+};
+use egui::{Rect, pos2};
+
+/// Provides tiles from a closure, e.g. generated procedurally or read from embedded assets,
+/// without implementing the whole [`Tiles`] trait.
+///
+/// The closure is called every frame for every visible tile, so it should be cheap. Keep
+/// textures around and return their clones instead of loading them each time.
+///
+/// ```
+/// use walkers::{FnTiles, Tile, TileId};
+///
+/// let ctx = egui::Context::default();
+/// let texture = ctx.load_texture("tile", egui::ColorImage::example(), Default::default());
+///
+/// let tiles = FnTiles::new(move |_tile_id: TileId| Some(Tile::Raster(texture.clone())), 256);
+/// ```
+pub struct FnTiles<F, P = MercatorProjection> {
+    tile_fn: F,
+    tile_size: u32,
+    attribution: Attribution,
+    projection: P,
+}
+
+impl<F> FnTiles<F>
+where
+    F: FnMut(TileId) -> Option<Tile>,
+{
+    /// Tiles of given size, returned by the `tile_fn` in the Mercator projection.
+    pub fn new(tile_fn: F, tile_size: u32) -> Self {
+        Self {
+            tile_fn,
+            tile_size,
+            attribution: Attribution {
+                text: "",
+                url: "",
+                logo_light: None,
+                logo_dark: None,
+            },
+            projection: MercatorProjection,
+        }
+    }
+}
+
+impl<F, P> FnTiles<F, P>
+where
+    F: FnMut(TileId) -> Option<Tile>,
+    P: Projection,
+{
+    /// Use tiles in a projection other than Mercator.
+    pub fn with_projection<Q: Projection>(self, projection: Q) -> FnTiles<F, Q> {
+        FnTiles {
+            tile_fn: self.tile_fn,
+            tile_size: self.tile_size,
+            attribution: self.attribution,
+            projection,
+        }
+    }
+
+    /// Set the attribution shown for these tiles. There is none by default.
+    pub fn with_attribution(mut self, attribution: Attribution) -> Self {
+        self.attribution = attribution;
+        self
+    }
+
+    pub fn projection(&self) -> &P {
+        &self.projection
+    }
+}
+
+impl<F, P> Tiles for FnTiles<F, P>
+where
+    F: FnMut(TileId) -> Option<Tile>,
+    P: Projection,
+{
+    type Projection = P;
+
+    fn at(&mut self, tile_id: TileId) -> Option<TilePiece> {
+        if !tile_id.valid() {
+            return None;
+        }
+
+        let tile = (self.tile_fn)(tile_id)?;
+        Some(TilePiece::new(
+            tile,
+            Rect::from_min_max(pos2(0., 0.), pos2(1., 1.)),
+        ))
+    }
+
+    fn attribution(&self) -> Attribution {
+        self.attribution.clone()
+    }
+
+    fn tile_size(&self) -> u32 {
+        self.tile_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Map, MapMemory, lon_lat};
+    use egui::{Pos2, RawInput, Vec2};
+
+    fn requested_tiles(tile_size: u32, memory: &mut MapMemory) -> Vec<TileId> {
+        let ctx = egui::Context::default();
+        let mut requested = Vec::new();
+        let mut tiles = FnTiles::new(
+            |tile_id| {
+                requested.push(tile_id);
+                Some(Tile::Empty)
+            },
+            tile_size,
+        );
+
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, Vec2::new(400., 300.))),
+            ..Default::default()
+        };
+        let _ = ctx.run_ui(input, |ui| {
+            ui.add(
+                Map::new(MercatorProjection, memory, lon_lat(17., 51.)).with_layer(&mut tiles, 1.),
+            );
+        });
+
+        drop(tiles);
+        requested
+    }
+
+    #[test]
+    fn visible_tiles_are_taken_from_the_closure() {
+        let mut memory = MapMemory::default();
+        let requested = requested_tiles(256, &mut memory);
+
+        assert!(!requested.is_empty());
+        assert!(
+            requested
+                .iter()
+                .all(|tile_id| tile_id.zoom == memory.zoom_level())
+        );
+    }
+
+    #[test]
+    fn closure_gets_tiles_of_its_size() {
+        let mut memory = MapMemory::default();
+        let requested = requested_tiles(512, &mut memory);
+
+        assert!(!requested.is_empty());
+        assert!(
+            requested
+                .iter()
+                .all(|tile_id| tile_id.zoom == memory.zoom_level() - 1)
+        );
+    }
+
+    #[test]
+    fn whole_tile_is_drawn() {
+        let mut tiles = FnTiles::new(|_| Some(Tile::Empty), 256);
+        let piece = tiles
+            .at(TileId {
+                x: 1,
+                y: 1,
+                zoom: 1,
+            })
+            .expect("tile from the closure");
+
+        assert_eq!(piece.uv, Rect::from_min_max(pos2(0., 0.), pos2(1., 1.)));
+        assert!(
+            tiles
+                .at(TileId {
+                    x: 2,
+                    y: 0,
+                    zoom: 1
+                })
+                .is_none()
+        );
+    }
+}
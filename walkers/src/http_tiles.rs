@@ -1,16 +1,20 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
 use bytes::Bytes;
-use egui::Context;
-use reqwest_middleware::ClientWithMiddleware;
+use egui::{Context, TextureFilter, TextureOptions};
+use web_time::Instant;
 
-use crate::io::Fetch;
-use crate::io::http::http_client;
+use crate::io::http::{RateLimit, ReqwestFetcher, insert_headers, is_timeout};
 use crate::io::tiles_io::TilesIo;
+use crate::io::{Fetch, FetchError, HeaderMap, RateLimited, RequestHeaders, TileFetcher};
 use crate::projector::Projection;
 use crate::sources::{Attribution, TileSource};
 use crate::style::Style;
-use crate::tiles::{EguiTileFactory, interpolate_from_lower_zoom};
-use crate::{HttpOptions, TilePiece, Tiles};
-use crate::{Stats, TileId};
+use crate::tiles::{EguiTileFactory, Tile, interpolate_from_lower_zoom};
+use crate::{HttpOptions, Position, TilePiece, Tiles};
+use crate::{PreloadProgress, Stats, TileFailure, TileId};
 
 /// Downloads the tiles via HTTP. It must persist between frames.
 pub struct HttpTiles<P: Projection> {
@@ -18,7 +22,21 @@ pub struct HttpTiles<P: Projection> {
     tiles_io: TilesIo,
     projection: P,
     tile_size: u32,
+    min_zoom: u8,
     max_zoom: u8,
+    rate_limit: RateLimit,
+
+    /// How long newly loaded tiles take to fade in, if at all.
+    fade_in: Option<Duration>,
+
+    /// Tiles which are still fading in, along with the time they were loaded.
+    fading: HashMap<TileId, Instant>,
+
+    /// Options newly loaded tiles are uploaded to the GPU with.
+    texture_options: Arc<Mutex<TextureOptions>>,
+
+    /// Source the tiles are downloaded from, shared with the fetch loop.
+    source: SharedSource,
 }
 
 impl<P: Projection> HttpTiles<P> {
@@ -51,26 +69,175 @@ impl<P: Projection> HttpTiles<P> {
     {
         let attribution = source.attribution();
         let tile_size = source.tile_size();
+        let min_zoom = source.min_zoom();
         let max_zoom = source.max_zoom();
         let projection = source.projection();
+        let rate_limit = RateLimit::default();
+        let tile_factory = EguiTileFactory::new(egui_ctx.clone(), style);
+        let texture_options = tile_factory.texture_options();
+        let source: SharedSource = Arc::new(Mutex::new(Box::new(source)));
 
         Self {
             attribution,
             tiles_io: TilesIo::new(
-                HttpFetch::new(source, http_options),
-                EguiTileFactory::new(egui_ctx.clone(), style),
+                HttpFetch::new(source.clone(), http_options, rate_limit.clone()),
+                tile_factory,
                 egui_ctx,
             ),
             projection,
             tile_size,
+            min_zoom,
             max_zoom,
+            rate_limit,
+            fade_in: None,
+            fading: HashMap::new(),
+            texture_options,
+            source,
+        }
+    }
+
+    /// Switch to another source, e.g. from streets to satellite imagery, keeping the HTTP
+    /// client, options and styles. Tiles of the previous source are dropped from the cache,
+    /// including the ones which were being downloaded, so they never show up in place of the new
+    /// ones. On-disk [`HttpOptions::cache`] is keyed by URLs, so sources do not mix there either.
+    /// [`HttpTiles::with_max_zoom`] needs to be applied again, if it was used.
+    ///
+    /// Sources of different types can be switched between when boxed, i.e. as
+    /// `Box<dyn TileSource<Projection = P> + Send + Sync>`.
+    pub fn set_source<S>(&mut self, source: S)
+    where
+        S: TileSource<Projection = P> + Sync + Send + 'static,
+    {
+        self.attribution = source.attribution();
+        self.tile_size = source.tile_size();
+        self.min_zoom = source.min_zoom();
+        self.max_zoom = source.max_zoom();
+        self.projection = source.projection();
+
+        if let Ok(mut shared) = self.source.lock() {
+            *shared = Box::new(source);
+        }
+        self.clear_cache();
+    }
+
+    /// Override the highest zoom level the source has tiles for, see [`TileSource::max_zoom`].
+    /// Above it, tiles of this level are stretched instead of being downloaded.
+    pub fn with_max_zoom(mut self, max_zoom: u8) -> Self {
+        self.max_zoom = max_zoom;
+        self
+    }
+
+    /// Make newly loaded tiles fade in over the given `duration` instead of popping up at once.
+    /// Tiles which are already in the cache are drawn fully opaque. Disabled by default.
+    pub fn with_fade_in(mut self, duration: Duration) -> Self {
+        self.fade_in = Some(duration);
+        self
+    }
+
+    /// Filter used when raster tiles are scaled, e.g. [`TextureFilter::Nearest`] to keep pixels
+    /// crisp when overzooming categorical data, where blending colors makes no sense. It applies
+    /// to tiles loaded from now on. Default is [`TextureFilter::Linear`].
+    pub fn with_texture_filter(self, filter: TextureFilter) -> Self {
+        if let Ok(mut options) = self.texture_options.lock() {
+            options.magnification = filter;
+            options.minification = filter;
         }
+        self
     }
 
     pub fn stats(&self) -> Stats {
         self.tiles_io.stats()
     }
 
+    /// Tiles which failed to load for good since the last call, e.g. to tell the user that
+    /// imagery is not available for some area. Each tile is reported once, even if it gets
+    /// requested again later. Failed tiles are drawn as blank, or interpolated from a lower zoom
+    /// level if possible.
+    pub fn take_failures(&mut self) -> Vec<TileFailure> {
+        self.tiles_io.take_failures()
+    }
+
+    /// Forget all loaded tiles, so that they are downloaded again, e.g. after switching API keys
+    /// or when the imagery got outdated. Downloads in progress are restarted too.
+    ///
+    /// The on-disk [`HttpOptions::cache`] is left intact, so tiles which are still fresh
+    /// according to it are taken from there.
+    pub fn clear_cache(&mut self) {
+        self.tiles_io.clear();
+        self.fading.clear();
+    }
+
+    /// Download all tiles covering the area between `north_west` and `south_east` corners, from
+    /// `min_zoom` to `max_zoom`, e.g. before going offline. Lower zoom levels go first.
+    /// `on_progress` is called each time a tile is loaded or fails for good.
+    ///
+    /// Tiles are requested only when download slots are not taken by the visible ones, so
+    /// [`HttpOptions::max_parallel_downloads`] is respected and the map stays responsive. Like
+    /// other downloads, preloading moves forward while the map is being drawn. Preloading again
+    /// replaces the previous one, see also [`HttpTiles::cancel_preload`].
+    ///
+    /// Only a few hundred tiles are kept in memory, so for larger areas enable
+    /// [`HttpOptions::cache`] to keep them on disk. Note that the number of tiles grows fourfold
+    /// with each zoom level, and that many tile servers forbid bulk downloading in their usage
+    /// policies.
+    pub fn preload(
+        &mut self,
+        north_west: Position,
+        south_east: Position,
+        min_zoom: u8,
+        max_zoom: u8,
+        on_progress: impl FnMut(PreloadProgress) + Send + 'static,
+    ) {
+        let tile_ids: Vec<_> = (min_zoom.max(self.min_zoom)..=max_zoom.min(self.max_zoom))
+            .flat_map(|zoom| self.tiles_within(north_west, south_east, zoom))
+            .collect();
+        self.tiles_io.preload(tile_ids, on_progress);
+    }
+
+    /// Stop preloading started by [`HttpTiles::preload`]. Tiles which are already being
+    /// downloaded still land in the cache.
+    pub fn cancel_preload(&mut self) {
+        self.tiles_io.cancel_preload();
+    }
+
+    /// How far the ongoing [`HttpTiles::preload`] got, or `None` if there is nothing being
+    /// preloaded.
+    pub fn preload_progress(&self) -> Option<PreloadProgress> {
+        self.tiles_io.preload_progress()
+    }
+
+    /// Tiles of given zoom level covering the area between the corners. If the area crosses the
+    /// antimeridian, i.e. `south_east` is west of `north_west`, tiles wrap around.
+    fn tiles_within(&self, north_west: Position, south_east: Position, zoom: u8) -> Vec<TileId> {
+        let last = (1u32 << zoom) as f64 - 1.;
+        let [min, max] = [north_west, south_east].map(|position| {
+            let pixels = self.projection.position_to_pixels(position, zoom as f64);
+            (
+                (pixels.x() / 256.).floor().clamp(0., last) as u32,
+                (pixels.y() / 256.).floor().clamp(0., last) as u32,
+            )
+        });
+
+        let tiles = 1 << zoom;
+        let max_x = if max.0 < min.0 { max.0 + tiles } else { max.0 };
+
+        (min.0..=max_x)
+            .flat_map(|x| {
+                (min.1.min(max.1)..=min.1.max(max.1)).map(move |y| TileId {
+                    x: x % tiles,
+                    y,
+                    zoom,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the tile server responded with `429 Too Many Requests` recently. No new tiles
+    /// are requested until the time it asked to wait for passes.
+    pub fn rate_limited(&self) -> bool {
+        self.rate_limit.remaining().is_some()
+    }
+
     pub fn projection(&self) -> &P {
         &self.projection
     }
@@ -78,22 +245,55 @@ impl<P: Projection> HttpTiles<P> {
     /// Get at tile, or interpolate it from lower zoom levels. This function does not start any
     /// downloads.
     fn get_from_cache_or_interpolate(&mut self, tile_id: TileId) -> Option<TilePiece> {
-        let mut zoom_candidate = tile_id.zoom;
+        self.get_from_cache_or_interpolate_from(tile_id, tile_id.zoom)
+    }
 
+    /// Like [`Self::get_from_cache_or_interpolate`], but starting at the given zoom level.
+    fn get_from_cache_or_interpolate_from(
+        &mut self,
+        tile_id: TileId,
+        mut zoom_candidate: u8,
+    ) -> Option<TilePiece> {
         loop {
             let (zoomed_tile_id, uv) = interpolate_from_lower_zoom(tile_id, zoom_candidate);
 
             if let Some(Some(tile)) = self.tiles_io.cache.get(&zoomed_tile_id) {
-                break Some(TilePiece {
-                    tile: tile.clone(),
-                    uv,
-                });
+                let piece = TilePiece::new(tile.clone(), uv);
+                let opacity = self.opacity(zoomed_tile_id);
+
+                if opacity < 1. {
+                    // Keep showing what was there before, until the tile fades in completely.
+                    if let Some(placeholder) = zoom_candidate
+                        .checked_sub(1)
+                        .and_then(|zoom| self.get_from_cache_or_interpolate_from(tile_id, zoom))
+                    {
+                        break Some(piece.with_opacity(opacity).with_placeholder(placeholder));
+                    }
+                }
+
+                break Some(piece.with_opacity(opacity));
             }
 
             // Keep zooming out until we find a donor or there is no more zoom levels.
             zoom_candidate = zoom_candidate.checked_sub(1)?;
         }
     }
+
+    /// Opacity of a cached tile, which is below 1.0 while it is still fading in.
+    fn opacity(&mut self, tile_id: TileId) -> f32 {
+        let (Some(duration), Some(loaded)) = (self.fade_in, self.fading.get(&tile_id)) else {
+            return 1.;
+        };
+
+        let progress = loaded.elapsed().as_secs_f32() / duration.as_secs_f32();
+        if progress < 1. {
+            self.tiles_io.egui_ctx.request_repaint();
+            progress
+        } else {
+            self.fading.remove(&tile_id);
+            1.
+        }
+    }
 }
 
 impl<P: Projection> Tiles for HttpTiles<P> {
@@ -106,9 +306,17 @@ impl<P: Projection> Tiles for HttpTiles<P> {
 
     /// Return a tile if already in cache, schedule a download otherwise.
     fn at(&mut self, tile_id: TileId) -> Option<TilePiece> {
-        self.tiles_io.put_single_fetched_tile_in_cache();
+        if let Some(loaded) = self.tiles_io.put_single_fetched_tile_in_cache()
+            && let Some(duration) = self.fade_in
+            // There is nothing to fade in, and the placeholder should not linger underneath.
+            && !matches!(self.tiles_io.cache.peek(&loaded), Some(Some(Tile::Empty)))
+        {
+            // Forget tiles which got evicted from the cache before being drawn.
+            self.fading.retain(|_, since| since.elapsed() < duration);
+            self.fading.insert(loaded, Instant::now());
+        }
 
-        if !tile_id.valid() {
+        if !tile_id.valid() || tile_id.zoom < self.min_zoom {
             return None;
         }
 
@@ -118,75 +326,142 @@ impl<P: Projection> Tiles for HttpTiles<P> {
             tile_id
         };
 
-        self.tiles_io.make_sure_is_fetched(tile_id_to_download);
+        if let Some(remaining) = self.rate_limit.remaining() {
+            // Come back once the server lets us.
+            self.tiles_io.egui_ctx.request_repaint_after(remaining);
+        } else {
+            self.tiles_io.make_sure_is_fetched(tile_id_to_download);
+        }
+
         self.get_from_cache_or_interpolate(tile_id)
     }
 
     fn tile_size(&self) -> u32 {
         self.tile_size
     }
+
+    fn loading(&self) -> bool {
+        // Requests are not even queued while rate limited.
+        self.tiles_io.loading() || self.rate_limited()
+    }
 }
 
+/// How long to pause requests after being rate limited, if the server does not say.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(10);
+
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum HttpFetchError {
-    #[error(transparent)]
-    HttpMiddleware(#[from] reqwest_middleware::Error),
-    #[error(transparent)]
-    Http(#[from] reqwest::Error),
+    #[error("rate limited by the tile server")]
+    RateLimited,
+    #[error("{0}")]
+    TimedOut(FetchError),
+    #[error("{0}")]
+    Other(FetchError),
+}
+
+/// Part of [`TileSource`] needed by the fetch loop, which does not depend on the projection.
+trait TileUrl: Send + Sync {
+    fn url(&self, tile_id: TileId) -> String;
+}
+
+impl<S: TileSource + Send + Sync> TileUrl for S {
+    fn url(&self, tile_id: TileId) -> String {
+        self.tile_url(tile_id)
+    }
 }
 
-pub(crate) struct HttpFetch<S>
-where
-    S: TileSource + Send + 'static,
-{
-    source: S,
+/// Source which can be replaced while the fetch loop is running, see [`HttpTiles::set_source`].
+type SharedSource = Arc<Mutex<Box<dyn TileUrl>>>;
+
+pub(crate) struct HttpFetch {
+    source: SharedSource,
     max_concurrency: usize,
-    client: ClientWithMiddleware,
+    fetcher: Box<dyn TileFetcher>,
+    headers: HeaderMap,
+    request_headers: Option<RequestHeaders>,
+    rate_limit: RateLimit,
 }
 
-impl<S> HttpFetch<S>
-where
-    S: TileSource + Sync + Send,
-{
-    pub(crate) fn new(source: S, http_options: HttpOptions) -> Self {
+impl HttpFetch {
+    fn new(source: SharedSource, mut http_options: HttpOptions, rate_limit: RateLimit) -> Self {
+        let fetcher = http_options
+            .fetcher
+            .take()
+            .unwrap_or_else(|| Box::new(ReqwestFetcher::new(&http_options)));
+
+        let mut headers = HeaderMap::new();
+        insert_headers(&mut headers, http_options.headers);
+
         Self {
             source,
             max_concurrency: http_options.max_parallel_downloads.0,
-            client: http_client(&http_options),
+            fetcher,
+            headers,
+            request_headers: http_options.request_headers,
+            rate_limit,
         }
     }
 }
 
-impl<S> Fetch for HttpFetch<S>
-where
-    S: TileSource + Sync + Send,
-{
+impl Fetch for HttpFetch {
     type Error = HttpFetchError;
 
     async fn fetch(&self, tile_id: TileId) -> Result<Bytes, Self::Error> {
-        let url = self.source.tile_url(tile_id);
+        // Requests which were already queued when the server started to limit us.
+        if self.rate_limit.remaining().is_some() {
+            return Err(HttpFetchError::RateLimited);
+        }
+
+        let url = self
+            .source
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .url(tile_id);
         log::trace!("Downloading '{url}'.");
-        let image = self.client.get(&url).send().await?;
-        log::trace!("Downloaded '{}': {:?}.", url, image.status());
-        Ok(image.error_for_status()?.bytes().await?)
+
+        let mut headers = self.headers.clone();
+        if let Some(request_headers) = &self.request_headers {
+            insert_headers(&mut headers, request_headers(&tile_id, &url));
+        }
+
+        self.fetcher.fetch(url, headers).await.map_err(|err| {
+            if let Some(RateLimited { retry_after }) = err.downcast_ref() {
+                let retry_after = retry_after.unwrap_or(DEFAULT_RETRY_AFTER);
+                log::warn!("Rate limited by the tile server, pausing for {retry_after:?}.");
+                self.rate_limit.pause_for(retry_after);
+                HttpFetchError::RateLimited
+            } else if is_timeout(&err) {
+                HttpFetchError::TimedOut(err)
+            } else {
+                HttpFetchError::Other(err)
+            }
+        })
     }
 
     fn max_concurrency(&self) -> usize {
         self.max_concurrency
     }
+
+    fn retryable(&self, error: &Self::Error) -> bool {
+        matches!(
+            error,
+            HttpFetchError::RateLimited | HttpFetchError::TimedOut(_)
+        )
+    }
 }
 
+#[allow(clippy::unwrap_used)]
 #[cfg(test)]
 mod tests {
     use crate::MaxParallelDownloads;
     use crate::projector::MercatorProjection;
 
     use super::*;
+    use egui::{Rect, pos2};
     use hypermocker::{
         Bytes, StatusCode,
         hyper::header::{self, HeaderValue},
     };
-    use std::time::Duration;
 
     static TILE_ID: TileId = TileId {
         x: 1,
@@ -275,6 +550,172 @@ mod tests {
         assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
     }
 
+    #[tokio::test]
+    async fn loading_until_tiles_are_downloaded_or_failed() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let mut tiles = HttpTiles::new(source, Context::default());
+        assert!(!tiles.loading());
+
+        let mut anticipated = server.anticipate("/3/1/2.png").await;
+        assert!(tiles.at(TILE_ID).is_none());
+        assert!(tiles.loading());
+
+        anticipated.expect().await;
+        anticipated
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
+        assert!(!tiles.loading());
+
+        let missing = TileId {
+            x: 0,
+            y: 2,
+            zoom: 3,
+        };
+        let mut anticipated = server.anticipate("/3/0/2.png").await;
+        assert!(tiles.at(missing).is_none());
+        assert!(tiles.loading());
+
+        anticipated.expect().await;
+        anticipated.respond_with_status(StatusCode::NOT_FOUND).await;
+        while tiles.loading() {
+            assert!(tiles.at(missing).is_none());
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn new_tiles_fade_in() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let mut anticipated = server.anticipate("/3/1/2.png").await;
+
+        let mut tiles =
+            HttpTiles::new(source, Context::default()).with_fade_in(Duration::from_millis(200));
+
+        assert!(tiles.at(TILE_ID).is_none());
+        anticipated.expect().await;
+        anticipated
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
+
+        // Freshly loaded tile is still transparent.
+        assert!(tiles.at(TILE_ID).unwrap().opacity < 1.);
+
+        // After the fade, it stays opaque.
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(tiles.at(TILE_ID).unwrap().opacity, 1.);
+        assert_eq!(tiles.at(TILE_ID).unwrap().opacity, 1.);
+    }
+
+    #[tokio::test]
+    async fn missing_tile_is_replaced_by_a_piece_of_its_parent() {
+        let _ = env_logger::try_init();
+
+        let parent = TileId {
+            x: 0,
+            y: 1,
+            zoom: 2,
+        };
+
+        let (server, source) = hypermocker_mock().await;
+        let mut anticipated_parent = server.anticipate("/2/0/1.png").await;
+        let mut anticipated = server.anticipate("/3/1/2.png").await;
+
+        let mut tiles =
+            HttpTiles::new(source, Context::default()).with_fade_in(Duration::from_secs(60));
+
+        assert!(tiles.at(parent).is_none());
+        anticipated_parent.expect().await;
+        anticipated_parent
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        assert_tile_to_become_available_eventually(&mut tiles, parent).await;
+
+        // Parent has not faded in yet, but it has nothing to be drawn over anyway.
+        assert!(tiles.at(parent).unwrap().placeholder.is_none());
+
+        // Until the tile itself is downloaded, the matching quarter of its parent is used.
+        let quarter = Rect::from_min_max(pos2(0.5, 0.), pos2(1., 0.5));
+        assert_eq!(tiles.at(TILE_ID).unwrap().uv, quarter);
+
+        anticipated.expect().await;
+        anticipated
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+
+        while tiles.at(TILE_ID).unwrap().uv == quarter {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // While fading in, the parent's quarter is still drawn underneath.
+        let piece = tiles.at(TILE_ID).unwrap();
+        assert_eq!(piece.uv, Rect::from_min_max(pos2(0., 0.), pos2(1., 1.)));
+        assert!(piece.opacity < 1.);
+        assert_eq!(piece.placeholder.unwrap().uv, quarter);
+    }
+
+    #[tokio::test]
+    async fn tiles_above_max_zoom_are_stretched_from_lower_zoom() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let mut anticipated = server.anticipate("/2/0/1.png").await;
+
+        let mut tiles = HttpTiles::new(source, Context::default()).with_max_zoom(2);
+
+        assert!(tiles.at(TILE_ID).is_none());
+        anticipated.expect().await;
+        anticipated
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
+
+        assert_eq!(
+            tiles.at(TILE_ID).unwrap().uv,
+            Rect::from_min_max(pos2(0.5, 0.), pos2(1., 0.5))
+        );
+    }
+
+    #[tokio::test]
+    async fn download_is_not_started_below_min_zoom() {
+        let _ = env_logger::try_init();
+
+        struct MinZoomSource(TestSource);
+
+        impl TileSource for MinZoomSource {
+            type Projection = MercatorProjection;
+
+            fn projection(&self) -> MercatorProjection {
+                MercatorProjection
+            }
+
+            fn tile_url(&self, tile_id: TileId) -> String {
+                self.0.tile_url(tile_id)
+            }
+
+            fn attribution(&self) -> Attribution {
+                self.0.attribution()
+            }
+
+            fn min_zoom(&self) -> u8 {
+                4
+            }
+        }
+
+        let (_server, source) = hypermocker_mock().await;
+        let mut tiles = HttpTiles::new(MinZoomSource(source), Context::default());
+
+        assert!(tiles.at(TILE_ID).is_none());
+
+        // Make sure it does not come.
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
     #[tokio::test]
     async fn download_is_not_started_when_tile_is_invalid() {
         let _ = env_logger::try_init();
@@ -320,6 +761,79 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn static_and_per_request_headers_are_merged() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let mut anticipated = server.anticipate("/3/1/2.png").await;
+
+        let mut tiles = HttpTiles::with_options(
+            source,
+            HttpOptions {
+                headers: vec![
+                    (
+                        "X-Api-Key".to_string(),
+                        crate::HeaderValue::from_static("key"),
+                    ),
+                    (
+                        "Authorization".to_string(),
+                        crate::HeaderValue::from_static("overridden"),
+                    ),
+                ],
+                request_headers: Some(Box::new(|tile_id, url| {
+                    assert!(url.ends_with("/3/1/2.png"));
+                    vec![(
+                        "Authorization".to_string(),
+                        crate::HeaderValue::from_str(&format!("signed {}", tile_id.zoom)).unwrap(),
+                    )]
+                })),
+                ..Default::default()
+            },
+            Context::default(),
+        );
+
+        // Initiate the download.
+        tiles.at(TILE_ID);
+
+        let request = anticipated.expect().await;
+        assert_eq!(
+            request.headers().get("x-api-key"),
+            Some(&HeaderValue::from_static("key"))
+        );
+        assert_eq!(
+            request.headers().get(header::AUTHORIZATION),
+            Some(&HeaderValue::from_static("signed 3"))
+        );
+    }
+
+    #[tokio::test]
+    async fn request_is_retried_after_timeout() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let mut tiles = HttpTiles::with_options(
+            source,
+            HttpOptions {
+                timeout: Duration::from_millis(100),
+                ..Default::default()
+            },
+            Context::default(),
+        );
+
+        // Server never responds to the first request.
+        let mut first = server.anticipate("/3/1/2.png").await;
+        assert!(tiles.at(TILE_ID).is_none());
+        first.expect().await;
+
+        server
+            .anticipate("/3/1/2.png")
+            .await
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
+    }
+
     #[tokio::test]
     async fn by_default_there_can_be_6_parallel_downloads_at_most() {
         let _ = env_logger::try_init();
@@ -389,43 +903,530 @@ mod tests {
         awaiting_request.expect().await;
     }
 
-    async fn assert_tile_is_empty_forever(tiles: &mut HttpTiles<MercatorProjection>) {
-        // Should be None now, and forever.
-        assert!(tiles.at(TILE_ID).is_none());
-        tokio::time::sleep(Duration::from_secs(1)).await;
-        assert!(tiles.at(TILE_ID).is_none());
-    }
-
     #[tokio::test]
-    async fn tile_is_empty_forever_if_http_returns_error() {
+    async fn tiles_which_went_out_of_view_are_not_downloaded() {
         let _ = env_logger::try_init();
 
         let (server, source) = hypermocker_mock().await;
-        let mut tiles = HttpTiles::new(source, Context::default());
-        server
-            .anticipate("/3/1/2.png")
-            .await
-            .respond_with_status(StatusCode::NOT_FOUND)
+        let egui_ctx = Context::default();
+        let mut tiles = HttpTiles::with_options(
+            source,
+            HttpOptions {
+                max_parallel_downloads:
+                    MaxParallelDownloads::value_manually_confirmed_with_provider_limits(1),
+                ..Default::default()
+            },
+            egui_ctx.clone(),
+        );
+
+        // Occupy the only download slot.
+        let mut first = server.anticipate("/3/1/2.png").await;
+        tiles.at(TILE_ID);
+        first.expect().await;
+
+        // One of these fits into the channel to the fetch loop, the other one waits in the queue.
+        let mut accepted = server.anticipate("/10/1/1.png").await;
+        tiles.at(TileId {
+            x: 1,
+            y: 1,
+            zoom: 10,
+        });
+        tiles.at(TileId {
+            x: 2,
+            y: 1,
+            zoom: 10,
+        });
+        assert_eq!(tiles.stats().queued, 1);
+
+        // Couple of frames later, only another tile is visible.
+        let _ = egui_ctx.run_ui(Default::default(), |_| {});
+        let _ = egui_ctx.run_ui(Default::default(), |_| {});
+        tiles.at(TileId {
+            x: 3,
+            y: 1,
+            zoom: 10,
+        });
+        assert_eq!(tiles.stats().queued, 1);
+
+        first
+            .respond(Bytes::from_static(include_bytes!(
+                "../assets/blank-255-tile.png"
+            )))
             .await;
+        accepted.expect().await;
 
-        assert_tile_is_empty_forever(&mut tiles).await;
+        // Download of 2/1 is never started. Server would panic on unexpected request.
+        let mut visible = server.anticipate("/10/3/1.png").await;
+        accepted
+            .respond(Bytes::from_static(include_bytes!(
+                "../assets/blank-255-tile.png"
+            )))
+            .await;
+        while tiles.stats().queued > 0 {
+            tiles.at(TileId {
+                x: 3,
+                y: 1,
+                zoom: 10,
+            });
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        visible.expect().await;
     }
 
     #[tokio::test]
-    async fn tile_is_empty_forever_if_http_returns_no_body() {
+    async fn not_loading_after_panning_back_to_cached_tiles() {
         let _ = env_logger::try_init();
 
         let (server, source) = hypermocker_mock().await;
-        let mut tiles = HttpTiles::new(source, Context::default());
-        server
-            .anticipate("/3/1/2.png")
-            .await
-            .respond_with_status(StatusCode::OK)
+        let egui_ctx = Context::default();
+        let mut tiles = HttpTiles::with_options(
+            source,
+            HttpOptions {
+                max_parallel_downloads:
+                    MaxParallelDownloads::value_manually_confirmed_with_provider_limits(1),
+                ..Default::default()
+            },
+            egui_ctx.clone(),
+        );
+
+        server
+            .anticipate("/3/1/2.png")
+            .await
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
+        assert!(!tiles.loading());
+
+        // Pan away, so that one tile occupies the only download slot, one fits into the channel
+        // to the fetch loop and the last one waits in the queue.
+        let _ = egui_ctx.run_ui(Default::default(), |_| {});
+        let mut occupying = server.anticipate("/10/1/1.png").await;
+        server
+            .anticipate("/10/2/1.png")
+            .await
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        tiles.at(TileId {
+            x: 1,
+            y: 1,
+            zoom: 10,
+        });
+        occupying.expect().await;
+        for x in 2..=3 {
+            tiles.at(TileId { x, y: 1, zoom: 10 });
+        }
+        assert_eq!(tiles.stats().queued, 1);
+
+        // Couple of frames later, only the cached tile is visible again. Download of 3/1 is never
+        // started. Server would panic on unexpected request.
+        let _ = egui_ctx.run_ui(Default::default(), |_| {});
+        let _ = egui_ctx.run_ui(Default::default(), |_| {});
+        assert!(tiles.at(TILE_ID).is_some());
+        occupying
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while tiles.loading() {
+                assert!(tiles.at(TILE_ID).is_some());
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("loading to finish");
+        assert_eq!(tiles.stats().queued, 0);
+    }
+
+    #[tokio::test]
+    async fn area_is_preloaded_with_progress() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        for url in [
+            "/0/0/0.png",
+            "/1/0/0.png",
+            "/1/0/1.png",
+            "/1/1/0.png",
+            "/1/1/1.png",
+        ] {
+            server
+                .anticipate(url)
+                .await
+                .respond(include_bytes!("../assets/blank-255-tile.png"))
+                .await;
+        }
+
+        let mut tiles = HttpTiles::new(source, Context::default());
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let reported = progress.clone();
+        tiles.preload(
+            crate::lon_lat(-180., 85.),
+            crate::lon_lat(180., -85.),
+            0,
+            1,
+            move |progress| reported.lock().unwrap().push(progress.done),
+        );
+        assert_eq!(
+            tiles.preload_progress(),
+            Some(PreloadProgress { done: 0, total: 5 })
+        );
+
+        // Preloading moves forward while the map is being drawn.
+        let root = TileId {
+            x: 0,
+            y: 0,
+            zoom: 0,
+        };
+        while tiles.preload_progress().is_some() {
+            tiles.at(root);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(*progress.lock().unwrap(), vec![1, 2, 3, 4, 5]);
+        assert!(
+            tiles
+                .at(TileId {
+                    x: 1,
+                    y: 1,
+                    zoom: 1
+                })
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn preloading_can_be_canceled() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let mut first = server.anticipate("/1/0/0.png").await;
+
+        // Next one might make it into the channel to the fetch loop before being canceled.
+        server
+            .anticipate("/1/0/1.png")
+            .await
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+
+        let mut tiles = HttpTiles::with_options(
+            source,
+            HttpOptions {
+                max_parallel_downloads:
+                    MaxParallelDownloads::value_manually_confirmed_with_provider_limits(1),
+                ..Default::default()
+            },
+            Context::default(),
+        );
+        let progress = Arc::new(Mutex::new(0));
+        let reported = progress.clone();
+        tiles.preload(
+            crate::lon_lat(-180., 85.),
+            crate::lon_lat(180., -85.),
+            1,
+            1,
+            move |_| *reported.lock().unwrap() += 1,
+        );
+        first.expect().await;
+
+        tiles.cancel_preload();
+        assert!(tiles.preload_progress().is_none());
+
+        // Tile which was already requested still lands in the cache.
+        first
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        let tile_id = TileId {
+            x: 0,
+            y: 0,
+            zoom: 1,
+        };
+        assert_tile_to_become_available_eventually(&mut tiles, tile_id).await;
+
+        // Remaining ones are not requested, server would panic otherwise.
+        for _ in 0..10 {
+            tiles.at(tile_id);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(*progress.lock().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn downloads_of_tiles_which_went_out_of_view_are_canceled() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let egui_ctx = Context::default();
+        let mut tiles = HttpTiles::with_options(
+            source,
+            HttpOptions {
+                max_parallel_downloads:
+                    MaxParallelDownloads::value_manually_confirmed_with_provider_limits(1),
+                ..Default::default()
+            },
+            egui_ctx.clone(),
+        );
+
+        // Occupy the only download slot.
+        let mut first = server.anticipate("/3/1/2.png").await;
+        tiles.at(TILE_ID);
+        first.expect().await;
+
+        // In the next frames, only another tile is visible. It gets the slot, even though the
+        // server never responds to the first request.
+        let visible = TileId {
+            x: 1,
+            y: 1,
+            zoom: 10,
+        };
+        server
+            .anticipate("/10/1/1.png")
+            .await
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        while tiles.at(visible).is_none() {
+            let _ = egui_ctx.run_ui(Default::default(), |_| {});
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // Canceled tile is requested again once it is visible again.
+        let mut again = server.anticipate("/3/1/2.png").await;
+        for _ in 0..10 {
+            tiles.at(TILE_ID);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        again.expect().await;
+    }
+
+    #[tokio::test]
+    async fn tiles_are_downloaded_again_after_clearing_the_cache() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let mut tiles = HttpTiles::new(source, Context::default());
+
+        server
+            .anticipate("/3/1/2.png")
+            .await
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
+
+        tiles.clear_cache();
+        let mut again = server.anticipate("/3/1/2.png").await;
+        assert!(tiles.at(TILE_ID).is_none());
+        again.expect().await;
+
+        // Download which was in progress while clearing is restarted.
+        tiles.clear_cache();
+        let mut restarted = server.anticipate("/3/1/2.png").await;
+        for _ in 0..10 {
+            assert!(tiles.at(TILE_ID).is_none());
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        restarted.expect().await;
+        restarted
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
+    }
+
+    #[tokio::test]
+    async fn tiles_are_downloaded_from_the_new_source_after_switching() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let satellite = TestSource::new(format!("{}/satellite", source.base_url));
+        let mut tiles = HttpTiles::new(
+            Box::new(source) as Box<dyn TileSource<Projection = MercatorProjection> + Send + Sync>,
+            Context::default(),
+        );
+
+        server
+            .anticipate("/3/1/2.png")
+            .await
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
+
+        tiles.set_source(Box::new(satellite));
+        let mut switched = server.anticipate("/satellite/3/1/2.png").await;
+        assert!(tiles.at(TILE_ID).is_none());
+        switched.expect().await;
+    }
+
+    #[tokio::test]
+    async fn tiles_of_the_previous_source_do_not_show_up_after_switching() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let satellite = TestSource::new(format!("{}/satellite", source.base_url));
+        let mut tiles = HttpTiles::new(source, Context::default());
+
+        let mut streets = server.anticipate("/3/1/2.png").await;
+        assert!(tiles.at(TILE_ID).is_none());
+        streets.expect().await;
+
+        tiles.set_source(satellite);
+        let mut switched = server.anticipate("/satellite/3/1/2.png").await;
+        streets
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+
+        // Street tile which was being downloaded while switching is not used.
+        for _ in 0..10 {
+            assert!(tiles.at(TILE_ID).is_none());
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        switched.expect().await;
+        switched
+            .respond(include_bytes!("../assets/blank-255-tile.png"))
+            .await;
+        assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
+    }
+
+    #[tokio::test]
+    async fn requests_are_paused_when_rate_limited() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let mut tiles = HttpTiles::new(source, Context::default());
+
+        server
+            .anticipate("/3/1/2.png")
+            .await
+            .respond_with_status_and_headers(StatusCode::TOO_MANY_REQUESTS, &[("Retry-After", "1")])
+            .await;
+
+        assert!(tiles.at(TILE_ID).is_none());
+        while !tiles.rate_limited() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // Other tiles are not requested, server would panic otherwise.
+        assert!(
+            tiles
+                .at(TileId {
+                    x: 1,
+                    y: 1,
+                    zoom: 10
+                })
+                .is_none()
+        );
+
+        // Once the time passes, the rate limited tile is requested again.
+        let mut retried = server.anticipate("/3/1/2.png").await;
+        while tiles.rate_limited() {
+            assert!(tiles.at(TILE_ID).is_none());
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let mut later = server.anticipate("/10/1/1.png").await;
+        assert!(
+            tiles
+                .at(TileId {
+                    x: 1,
+                    y: 1,
+                    zoom: 10
+                })
+                .is_none()
+        );
+        later.expect().await;
+
+        tiles.at(TILE_ID);
+        retried.expect().await;
+        retried
+            .respond(Bytes::from_static(include_bytes!(
+                "../assets/blank-255-tile.png"
+            )))
+            .await;
+        assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
+        later.respond_with_status(StatusCode::NOT_FOUND).await;
+    }
+
+    async fn assert_tile_is_empty_forever(tiles: &mut HttpTiles<MercatorProjection>) {
+        // Should be None now, and forever.
+        assert!(tiles.at(TILE_ID).is_none());
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(tiles.at(TILE_ID).is_none());
+
+        // Failure is reported once.
+        let failures = tiles.take_failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].tile_id, TILE_ID);
+        assert!(tiles.at(TILE_ID).is_none());
+        assert!(tiles.take_failures().is_empty());
+    }
+
+    #[tokio::test]
+    async fn tile_is_empty_forever_if_http_returns_error() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let mut tiles = HttpTiles::new(source, Context::default());
+        server
+            .anticipate("/3/1/2.png")
+            .await
+            .respond_with_status(StatusCode::NOT_FOUND)
             .await;
 
         assert_tile_is_empty_forever(&mut tiles).await;
     }
 
+    async fn assert_tile_to_be_known_empty(tiles: &mut HttpTiles<MercatorProjection>) {
+        assert_tile_to_become_available_eventually(tiles, TILE_ID).await;
+        let piece = tiles.at(TILE_ID).expect("tile is cached");
+        assert!(matches!(piece.tile, Tile::Empty));
+
+        // It is neither a failure, nor requested again.
+        assert!(tiles.take_failures().is_empty());
+        assert!(!tiles.loading());
+    }
+
+    #[tokio::test]
+    async fn tile_is_known_empty_if_http_returns_no_body() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let mut tiles = HttpTiles::new(source, Context::default());
+        server
+            .anticipate("/3/1/2.png")
+            .await
+            .respond_with_status(StatusCode::OK)
+            .await;
+
+        assert_tile_to_be_known_empty(&mut tiles).await;
+    }
+
+    #[tokio::test]
+    async fn tile_is_known_empty_if_http_returns_no_content() {
+        let _ = env_logger::try_init();
+
+        let (server, source) = hypermocker_mock().await;
+        let mut tiles = HttpTiles::new(source, Context::default());
+        server
+            .anticipate("/3/1/2.png")
+            .await
+            .respond_with_status(StatusCode::NO_CONTENT)
+            .await;
+
+        assert_tile_to_be_known_empty(&mut tiles).await;
+    }
+
+    #[tokio::test]
+    async fn tile_is_known_empty_if_http_returns_transparent_image() {
+        let _ = env_logger::try_init();
+
+        let mut png = Vec::new();
+        image::RgbaImage::new(256, 256)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        let (server, source) = hypermocker_mock().await;
+        let mut tiles = HttpTiles::new(source, Context::default());
+        server.anticipate("/3/1/2.png").await.respond(png).await;
+
+        assert_tile_to_be_known_empty(&mut tiles).await;
+    }
+
     #[tokio::test]
     async fn tile_is_empty_forever_if_http_returns_garbage() {
         let _ = env_logger::try_init();
@@ -441,6 +1442,80 @@ mod tests {
         assert_tile_is_empty_forever(&mut tiles).await;
     }
 
+    /// Serves the same tile for every URL, remembering what was requested.
+    #[derive(Default)]
+    struct RecordingFetcher {
+        urls: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl TileFetcher for RecordingFetcher {
+        fn fetch(&self, url: String, _headers: HeaderMap) -> crate::FetchFuture<'_> {
+            self.urls.lock().unwrap().push(url);
+            Box::pin(async {
+                Ok(Bytes::from_static(include_bytes!(
+                    "../assets/blank-255-tile.png"
+                )))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_fetcher_is_used_instead_of_the_built_in_one() {
+        let _ = env_logger::try_init();
+
+        let fetcher = RecordingFetcher::default();
+        let urls = fetcher.urls.clone();
+
+        let mut tiles = HttpTiles::with_options(
+            TestSource::new("https://example.com".to_string()),
+            HttpOptions {
+                fetcher: Some(Box::new(fetcher)),
+                ..Default::default()
+            },
+            Context::default(),
+        );
+
+        assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
+        assert_eq!(
+            *urls.lock().unwrap(),
+            vec!["https://example.com/3/1/2.png".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn tiles_are_uploaded_with_given_texture_filter() {
+        let _ = env_logger::try_init();
+
+        let ctx = Context::default();
+        let mut tiles = HttpTiles::with_options(
+            TestSource::new("https://example.com".to_string()),
+            HttpOptions {
+                fetcher: Some(Box::new(RecordingFetcher::default())),
+                ..Default::default()
+            },
+            ctx.clone(),
+        )
+        .with_texture_filter(TextureFilter::Nearest);
+
+        assert_tile_to_become_available_eventually(&mut tiles, TILE_ID).await;
+        let Some(TilePiece {
+            tile: Tile::Raster(texture),
+            ..
+        }) = tiles.at(TILE_ID)
+        else {
+            panic!("tile should be a raster one");
+        };
+
+        let meta = ctx
+            .tex_manager()
+            .read()
+            .meta(texture.id())
+            .cloned()
+            .unwrap();
+        assert_eq!(meta.options.magnification, TextureFilter::Nearest);
+        assert_eq!(meta.options.minification, TextureFilter::Nearest);
+    }
+
     /// Tile source, which gives invalid urls.
     struct GarbageSource;
 
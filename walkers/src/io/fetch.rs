@@ -7,9 +7,10 @@ use egui::Context;
 use futures::{
     SinkExt, StreamExt,
     channel::mpsc::{Receiver, Sender},
-    future::{Either, select, select_all},
+    future::{AbortHandle, Abortable, Either, select, select_all},
 };
 /// Asynchronous fetching loop.
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 #[derive(Debug, thiserror::Error)]
@@ -26,6 +27,12 @@ pub(super) enum Error {
     #[error("Fetch error: {0}")]
     Fetch(String),
 
+    #[error("Fetch error, will retry later: {0}")]
+    Retryable(String),
+
+    #[error("Download was canceled.")]
+    Canceled,
+
     #[error(transparent)]
     Tile(#[from] TileError),
 
@@ -49,6 +56,15 @@ impl<T> From<std::sync::PoisonError<T>> for Error {
     }
 }
 
+/// Outcome of fetching a tile, delivered to the main thread.
+pub(crate) enum Fetched {
+    Loaded(Tile),
+    /// Tile should be requested again later.
+    Retry,
+    /// Tile failed for good, with the error message.
+    Failed(String),
+}
+
 pub(crate) trait TileFactory {
     fn create_tile(&self, data: &Bytes, zoom: u8) -> Result<Tile, TileError>;
 }
@@ -58,31 +74,71 @@ async fn fetch_and_decode(
     fetch: &impl Fetch,
     tile_id: TileId,
     tile_factory: &impl TileFactory,
-) -> Result<(TileId, Tile), Error> {
-    let data = fetch
-        .fetch(tile_id)
+) -> (TileId, Result<Tile, Error>) {
+    let result = match fetch.fetch(tile_id).await {
+        // Sparse sources respond with no content where there is no data. That is not an error,
+        // the tile is just known to be empty.
+        Ok(data) if data.is_empty() => Ok(Tile::Empty),
+        Ok(data) => tile_factory
+            .create_tile(&data, tile_id.zoom)
+            .map_err(Error::from),
+        Err(e) if fetch.retryable(&e) => Err(Error::Retryable(e.to_string())),
+        Err(e) => Err(Error::Fetch(e.to_string())),
+    };
+    (tile_id, result)
+}
+
+/// Downloads which can be canceled by the main thread, e.g. because their tiles went out of view.
+pub(crate) type Downloads = Arc<Mutex<HashMap<TileId, AbortHandle>>>;
+
+/// Like [`fetch_and_decode`], but can be canceled via `downloads`.
+async fn fetch_and_decode_abortable(
+    fetch: &impl Fetch,
+    tile_id: TileId,
+    tile_factory: &impl TileFactory,
+    downloads: &Downloads,
+) -> (TileId, Result<Tile, Error>) {
+    let (handle, registration) = AbortHandle::new_pair();
+    if let Ok(mut downloads) = downloads.lock() {
+        downloads.insert(tile_id, handle);
+    }
+
+    let result = Abortable::new(fetch_and_decode(fetch, tile_id, tile_factory), registration)
         .await
-        .map_err(|e| Error::Fetch(e.to_string()))?;
-    Ok(tile_factory
-        .create_tile(&data, tile_id.zoom)
-        .map(|tile| (tile_id, tile))?)
+        .unwrap_or((tile_id, Err(Error::Canceled)));
+
+    if let Ok(mut downloads) = downloads.lock() {
+        downloads.remove(&tile_id);
+    }
+    result
 }
 
-/// Deliver the fetched tile to the main thread.
+/// Deliver the outcome of fetching the tile to the main thread.
 async fn fetch_complete(
-    mut tile_tx: Sender<(TileId, Tile)>,
+    mut tile_tx: Sender<(TileId, Fetched)>,
     egui_ctx: Context,
-    result: Result<(TileId, Tile), Error>,
+    (tile_id, result): (TileId, Result<Tile, Error>),
 ) -> Result<(), Error> {
     match result {
-        Ok((tile_id, tile)) => {
-            tile_tx.send((tile_id, tile)).await?;
+        Ok(tile) => {
+            tile_tx.send((tile_id, Fetched::Loaded(tile))).await?;
+            egui_ctx.request_repaint();
+        }
+        Err(e @ (Error::Retryable(_) | Error::Canceled)) => {
+            log::debug!("{tile_id:?}: {e}");
+            tile_tx.send((tile_id, Fetched::Retry)).await?;
             egui_ctx.request_repaint();
         }
         Err(e) => {
             // It would probably be more consistent to push it to the caller, but it's not that
             // important right now.
             log::warn!("{e}");
+            tile_tx
+                .send((tile_id, Fetched::Failed(e.to_string())))
+                .await?;
+
+            // Download slot got freed, so queued requests can go.
+            egui_ctx.request_repaint();
         }
     };
 
@@ -93,9 +149,10 @@ async fn fetch_continuously_impl(
     fetch: impl Fetch,
     stats: Arc<Mutex<Stats>>,
     mut request_rx: Receiver<TileId>,
-    tile_tx: Sender<(TileId, Tile)>,
+    tile_tx: Sender<(TileId, Fetched)>,
     tile_factory: impl TileFactory,
     egui_ctx: Context,
+    downloads: Downloads,
 ) -> Result<(), Error> {
     let mut outstanding = Vec::new();
 
@@ -103,7 +160,7 @@ async fn fetch_continuously_impl(
         if outstanding.is_empty() {
             // Only new fetches might be requested.
             let tile_id = request_rx.next().await.ok_or(Error::RequestChannelBroken)?;
-            let f = fetch_and_decode(&fetch, tile_id, &tile_factory);
+            let f = fetch_and_decode_abortable(&fetch, tile_id, &tile_factory, &downloads);
             outstanding.push(Box::pin(f));
         } else if outstanding.len() < fetch.max_concurrency() {
             // New fetches might be requested or ongoing fetches might be completed.
@@ -111,7 +168,7 @@ async fn fetch_continuously_impl(
                 // New fetch was requested.
                 Either::Left((request, remaining)) => {
                     let tile_id = request.ok_or(Error::RequestChannelBroken)?;
-                    let f = fetch_and_decode(&fetch, tile_id, &tile_factory);
+                    let f = fetch_and_decode_abortable(&fetch, tile_id, &tile_factory, &downloads);
                     outstanding = remaining.into_inner();
                     outstanding.push(Box::pin(f));
                 }
@@ -139,11 +196,22 @@ pub(crate) async fn fetch_continuously(
     fetch: impl Fetch,
     stats: Arc<Mutex<Stats>>,
     request_rx: Receiver<TileId>,
-    tile_tx: Sender<(TileId, Tile)>,
+    tile_tx: Sender<(TileId, Fetched)>,
     egui_ctx: Context,
     tile_factory: impl TileFactory,
+    downloads: Downloads,
 ) {
-    match fetch_continuously_impl(fetch, stats, request_rx, tile_tx, tile_factory, egui_ctx).await {
+    match fetch_continuously_impl(
+        fetch,
+        stats,
+        request_rx,
+        tile_tx,
+        tile_factory,
+        egui_ctx,
+        downloads,
+    )
+    .await
+    {
         Ok(()) | Err(Error::TileChannelClosed) | Err(Error::RequestChannelBroken) => {
             log::debug!("Tile fetch loop finished.");
         }
@@ -163,4 +231,10 @@ pub(crate) trait Fetch {
     fn fetch(&self, tile_id: TileId) -> impl Future<Output = Result<Bytes, Self::Error>> + Send;
 
     fn max_concurrency(&self) -> usize;
+
+    /// Whether the tile should be requested again later after this error, as opposed to
+    /// failing for good.
+    fn retryable(&self, _error: &Self::Error) -> bool {
+        false
+    }
 }
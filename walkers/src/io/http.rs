@@ -1,6 +1,99 @@
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-pub use reqwest::header::HeaderValue;
+use bytes::Bytes;
+use reqwest::StatusCode;
+use reqwest_middleware::ClientWithMiddleware;
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::TileId;
+
+pub use reqwest::header::{HeaderMap, HeaderValue};
+
+/// Computes headers for a single tile request, given its [`TileId`] and URL.
+pub type RequestHeaders = Box<dyn Fn(&TileId, &str) -> Vec<(String, HeaderValue)> + Send + Sync>;
+
+/// Error returned by a [`TileFetcher`].
+pub type FetchError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Future returned by [`TileFetcher::fetch`].
+#[cfg(not(target_arch = "wasm32"))]
+pub type FetchFuture<'a> = futures::future::BoxFuture<'a, Result<Bytes, FetchError>>;
+
+/// Future returned by [`TileFetcher::fetch`].
+#[cfg(target_arch = "wasm32")]
+pub type FetchFuture<'a> = futures::future::LocalBoxFuture<'a, Result<Bytes, FetchError>>;
+
+/// Error a [`TileFetcher`] should return when the server responds with
+/// `429 Too Many Requests`. Requests to the source are then paused for `retry_after`, or for
+/// a while if it is not known.
+#[derive(Debug, thiserror::Error)]
+#[error("rate limited by the tile server")]
+pub struct RateLimited {
+    pub retry_after: Option<Duration>,
+}
+
+/// Parse the `Retry-After` header, which holds either a number of seconds or an HTTP date.
+pub(crate) fn parse_retry_after(value: &HeaderValue) -> Option<Duration> {
+    let value = value.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    // `httpdate` gives `std::time::SystemTime`, which cannot tell the current time on wasm.
+    let date = httpdate::parse_http_date(value)
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    Some(date.saturating_sub(now))
+}
+
+/// Whether the error returned by the built-in [`TileFetcher`] is caused by a timeout.
+pub(crate) fn is_timeout(error: &FetchError) -> bool {
+    if let Some(error) = error.downcast_ref::<reqwest_middleware::Error>() {
+        matches!(error, reqwest_middleware::Error::Reqwest(error) if error.is_timeout())
+    } else if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+        error.is_timeout()
+    } else {
+        false
+    }
+}
+
+/// Moment until which requests to a tile source are paused, shared by the IO thread, which
+/// learns about rate limits, and the main thread, which stops requesting tiles.
+#[derive(Clone, Default)]
+pub(crate) struct RateLimit(Arc<Mutex<Option<Instant>>>);
+
+impl RateLimit {
+    /// Pause requests for given duration, unless they are already paused for longer.
+    pub(crate) fn pause_for(&self, duration: Duration) {
+        if let Ok(mut until) = self.0.lock() {
+            let new_until = Instant::now() + duration;
+            if until.is_none_or(|until| until < new_until) {
+                *until = Some(new_until);
+            }
+        }
+    }
+
+    /// How long requests remain paused, or `None` if they are not.
+    pub(crate) fn remaining(&self) -> Option<Duration> {
+        let until = (*self.0.lock().ok()?)?;
+        let remaining = until.saturating_duration_since(Instant::now());
+        (!remaining.is_zero()).then_some(remaining)
+    }
+}
+
+/// Performs HTTP requests on behalf of [`crate::HttpTiles`]. Implement it to download tiles
+/// with your own HTTP client, e.g. to sign requests or to route them through a proxy.
+pub trait TileFetcher: Send + Sync {
+    /// Download the body of the resource at `url`, sending given `headers` along. Responses
+    /// with an error status should result in an error, [`RateLimited`] in case of
+    /// `429 Too Many Requests`.
+    fn fetch(&self, url: String, headers: HeaderMap) -> FetchFuture<'_>;
+}
 
 /// Controls how [`crate::HttpTiles`] use the HTTP protocol, such as caching.
 pub struct HttpOptions {
@@ -20,11 +113,30 @@ pub struct HttpOptions {
     /// targets, and trying to set a different one may upset some servers (e.g. MapBox)
     pub user_agent: Option<HeaderValue>,
 
+    /// Time after which a tile request is abandoned, so that a dead server does not keep the
+    /// tile loading forever. The tile is requested again if it is still needed.
+    ///
+    /// This option is ignored in WASM, as timeouts are controlled by the browser.
+    pub timeout: Duration,
+
     /// Maximum number of parallel downloads.
     ///
     /// Many services have rate limits, and exceeding them may result in throttling, bans, or
     /// degraded service. Use the default value when in doubt.
     pub max_parallel_downloads: MaxParallelDownloads,
+
+    /// Headers sent along with every tile request, e.g. an API key.
+    pub headers: Vec<(String, HeaderValue)>,
+
+    /// Headers computed for each tile request separately, e.g. to sign it. They are merged with
+    /// [`HttpOptions::headers`], taking precedence over them.
+    pub request_headers: Option<RequestHeaders>,
+
+    /// Custom HTTP client to download tiles with. If `None`, a built-in one is used.
+    ///
+    /// [`HttpOptions::cache`], [`HttpOptions::user_agent`] and [`HttpOptions::timeout`] configure
+    /// the built-in client, so they are ignored when this is set.
+    pub fetcher: Option<Box<dyn TileFetcher>>,
 }
 
 impl Default for HttpOptions {
@@ -42,7 +154,11 @@ impl Default for HttpOptions {
         Self {
             cache: None,
             user_agent,
+            timeout: Duration::from_secs(30),
             max_parallel_downloads: MaxParallelDownloads::default(),
+            headers: Vec::new(),
+            request_headers: None,
+            fetcher: None,
         }
     }
 }
@@ -69,6 +185,50 @@ impl MaxParallelDownloads {
     }
 }
 
+/// Put given headers into the map, replacing ones of the same name. Invalid names are skipped.
+pub(crate) fn insert_headers(map: &mut HeaderMap, headers: Vec<(String, HeaderValue)>) {
+    for (name, value) in headers {
+        match reqwest::header::HeaderName::try_from(name) {
+            Ok(name) => {
+                map.insert(name, value);
+            }
+            Err(err) => log::warn!("Skipping invalid header: {err}."),
+        }
+    }
+}
+
+/// Built-in [`TileFetcher`], using `reqwest` configured by [`HttpOptions`].
+pub(crate) struct ReqwestFetcher {
+    client: ClientWithMiddleware,
+}
+
+impl ReqwestFetcher {
+    pub(crate) fn new(http_options: &HttpOptions) -> Self {
+        Self {
+            client: http_client(http_options),
+        }
+    }
+}
+
+impl TileFetcher for ReqwestFetcher {
+    fn fetch(&self, url: String, headers: HeaderMap) -> FetchFuture<'_> {
+        Box::pin(async move {
+            let response = self.client.get(&url).headers(headers).send().await?;
+            log::trace!("Downloaded '{}': {:?}.", url, response.status());
+
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                let retry_after = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(parse_retry_after);
+                return Err(RateLimited { retry_after }.into());
+            }
+
+            Ok(response.error_for_status()?.bytes().await?)
+        })
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub(crate) use native::*;
 
@@ -122,7 +282,50 @@ fn bare_client(http_options: &HttpOptions) -> reqwest::Client {
         builder = builder.user_agent(user_agent);
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        builder = builder.timeout(http_options.timeout);
+    }
+
     builder
         .build()
         .expect("could not initialize reqwest client")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_retry_after() {
+        assert_eq!(
+            parse_retry_after(&HeaderValue::from_static("120")),
+            Some(Duration::from_secs(120))
+        );
+
+        let in_a_minute = std::time::SystemTime::now() + Duration::from_secs(60);
+        let parsed = parse_retry_after(
+            &HeaderValue::from_str(&httpdate::fmt_http_date(in_a_minute)).expect("valid header"),
+        )
+        .expect("valid date");
+        assert!(parsed > Duration::from_secs(55) && parsed <= Duration::from_secs(60));
+
+        // Dates in the past mean no waiting.
+        assert_eq!(
+            parse_retry_after(&HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT")),
+            Some(Duration::ZERO)
+        );
+
+        assert_eq!(parse_retry_after(&HeaderValue::from_static("soon")), None);
+    }
+
+    #[test]
+    fn pausing_requests() {
+        let rate_limit = RateLimit::default();
+        assert_eq!(rate_limit.remaining(), None);
+
+        rate_limit.pause_for(Duration::from_secs(60));
+        rate_limit.pause_for(Duration::from_secs(1));
+        assert!(rate_limit.remaining().expect("should be paused") > Duration::from_secs(55));
+    }
+}
@@ -4,4 +4,7 @@ mod runtime;
 pub(crate) mod tiles_io;
 
 pub(crate) use fetch::{Fetch, TileFactory};
-pub use http::{HeaderValue, MaxParallelDownloads};
+pub use http::{
+    FetchError, FetchFuture, HeaderMap, HeaderValue, MaxParallelDownloads, RateLimited,
+    RequestHeaders, TileFetcher,
+};
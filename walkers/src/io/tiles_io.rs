@@ -1,25 +1,60 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::{Arc, Mutex};
 
 use egui::Context;
-use futures::channel::mpsc::{Receiver, Sender, TryRecvError, TrySendError, channel};
+use futures::channel::mpsc::{Receiver, Sender, TryRecvError, channel};
 use lru::LruCache;
 
 use crate::{
     Tile, TileId,
     io::{
         Fetch,
-        fetch::{TileFactory, fetch_continuously},
+        fetch::{Downloads, Fetched, TileFactory, fetch_continuously},
         runtime::Runtime,
     },
 };
 
+/// How many failures are kept until they are taken.
+const MAX_FAILURES: usize = 256;
+
 /// Asynchronously load and cache tiles from different local and remote sources.
 pub(crate) struct TilesIo {
     /// Tiles to be fetched by the IO thread.
     request_tx: Sender<TileId>,
 
+    /// Tiles waiting for the IO thread to accept them, along with the egui pass they were last
+    /// requested in. Tiles which are not requested anymore, e.g. because they went out of view,
+    /// are dropped from here before being downloaded.
+    queue: HashMap<TileId, u64>,
+
+    pub egui_ctx: Context,
+
     /// Tiles that got fetched and should be put in the cache.
-    tile_rx: Receiver<(TileId, Tile)>,
+    tile_rx: Receiver<(TileId, Fetched)>,
+
+    /// Tiles passed to the IO thread, which did not come back yet, along with the egui pass they
+    /// were last requested in.
+    in_flight: HashMap<TileId, u64>,
+
+    /// Downloads in progress, so that the ones which are not needed anymore can be canceled.
+    downloads: Downloads,
+
+    /// Last egui pass any tile was requested in.
+    last_pass: u64,
+
+    /// Tiles which failed for good since the last [`TilesIo::take_failures`].
+    failures: Vec<TileFailure>,
+
+    /// Tiles reported in `failures` already, so that they are not reported again when they get
+    /// requested again after being evicted from the cache.
+    reported_failures: LruCache<TileId, ()>,
+
+    /// Tiles which were being downloaded when the cache got cleared, so they might be outdated.
+    stale: HashSet<TileId>,
+
+    /// Tiles to be fetched in the background, see [`TilesIo::preload`].
+    preload: Option<Preload>,
 
     pub cache: LruCache<TileId, Option<Tile>>,
     pub stats: Arc<Mutex<Stats>>,
@@ -34,13 +69,13 @@ impl TilesIo {
         tile_factory: impl TileFactory + Send + Sync + 'static,
         egui_ctx: Context,
     ) -> Self {
-        let stats = Arc::new(Mutex::new(Stats { in_progress: 0 }));
-
-        // This ensures that newer requests are prioritized.
-        let channel_size = fetch.max_concurrency();
+        let stats = Arc::new(Mutex::new(Stats::default()));
+        let downloads = Downloads::default();
 
-        let (request_tx, request_rx) = channel(channel_size);
-        let (tile_tx, tile_rx) = channel(channel_size);
+        // Requests wait in the `queue` rather than in the channel, so that stale ones can be
+        // dropped and newer ones prioritized.
+        let (request_tx, request_rx) = channel(0);
+        let (tile_tx, tile_rx) = channel(fetch.max_concurrency());
 
         // This will run concurrently in a loop, handing downloads and talk with us via channels.
         let runtime = Runtime::new(fetch_continuously(
@@ -48,29 +83,75 @@ impl TilesIo {
             stats.clone(),
             request_rx,
             tile_tx,
-            egui_ctx,
+            egui_ctx.clone(),
             tile_factory,
+            downloads.clone(),
         ));
 
         // Just arbitrary value which seemed right.
         #[allow(clippy::unwrap_used)]
         let cache_size = std::num::NonZeroUsize::new(256).unwrap();
 
+        // Remember more failures than tiles, as they take little memory.
+        #[allow(clippy::unwrap_used)]
+        let reported_failures_size = std::num::NonZeroUsize::new(4096).unwrap();
+
         Self {
             cache: LruCache::new(cache_size),
             stats,
             request_tx,
+            queue: HashMap::new(),
+            egui_ctx,
             tile_rx,
+            in_flight: HashMap::new(),
+            downloads,
+            last_pass: 0,
+            failures: Vec::new(),
+            reported_failures: LruCache::new(reported_failures_size),
+            stale: HashSet::new(),
+            preload: None,
             runtime,
         }
     }
 
-    /// Takes a single fetched tile from the IO thread and puts it in the cache.
-    pub(crate) fn put_single_fetched_tile_in_cache(&mut self) {
+    /// Takes a single fetched tile from the IO thread and puts it in the cache. Returns its id if
+    /// a new tile was loaded.
+    pub(crate) fn put_single_fetched_tile_in_cache(&mut self) -> Option<TileId> {
         // This is called every frame, so take just one at the time.
-        match self.tile_rx.try_recv() {
-            Ok((tile_id, tile)) => {
+        let received = self.tile_rx.try_recv().map(|(tile_id, fetched)| {
+            if self.stale.remove(&tile_id) {
+                (tile_id, Fetched::Retry)
+            } else {
+                (tile_id, fetched)
+            }
+        });
+
+        match received {
+            Ok((tile_id, Fetched::Loaded(tile))) => {
+                self.in_flight.remove(&tile_id);
                 self.cache.put(tile_id, Some(tile));
+                self.preloaded(tile_id);
+                return Some(tile_id);
+            }
+            Ok((tile_id, Fetched::Retry)) => {
+                self.in_flight.remove(&tile_id);
+                self.cache.pop(&tile_id);
+                if let Some(preload) = &mut self.preload
+                    && preload.in_flight.remove(&tile_id)
+                {
+                    preload.pending.push_back(tile_id);
+                }
+            }
+            Ok((tile_id, Fetched::Failed(error))) => {
+                // Stays in the cache as `None`, so that it is not requested again.
+                self.in_flight.remove(&tile_id);
+                self.preloaded(tile_id);
+                // Do not pile them up if nobody takes them.
+                if self.reported_failures.put(tile_id, ()).is_none()
+                    && self.failures.len() < MAX_FAILURES
+                {
+                    self.failures.push(TileFailure { tile_id, error });
+                }
             }
             Err(TryRecvError::Empty) => {
                 // No new tile was downloaded, just ignore.
@@ -79,41 +160,272 @@ impl TilesIo {
                 log::error!("IO thread is dead");
             }
         }
+        None
     }
 
     /// Request a tile to be fetched, but only if it is not already being fetched.
     pub(crate) fn make_sure_is_fetched(&mut self, tile_id: TileId) {
-        match self.cache.try_get_or_insert(
-            tile_id,
-            || -> Result<Option<Tile>, TrySendError<TileId>> {
-                self.request_tx.try_send(tile_id)?;
-                log::trace!("Requested tile: {tile_id:?}");
-                Ok(None)
+        let pass = self.egui_ctx.cumulative_pass_nr();
+        if pass != self.last_pass {
+            self.cancel_stale_downloads();
+            self.forget_stale_requests(pass);
+            self.last_pass = pass;
+        }
+
+        if let Some(requested) = self.in_flight.get_mut(&tile_id) {
+            *requested = pass;
+        }
+
+        if self.cache.get(&tile_id).is_some() {
+            // Nothing new is visible, but free download slots can still be used for preloading.
+            if self.preload.is_some() {
+                self.send_queued(pass);
+            }
+            return;
+        }
+
+        self.queue.insert(tile_id, pass);
+        self.send_queued(pass);
+    }
+
+    /// Forget all tiles, so that they are fetched again. Ongoing downloads are canceled, and the
+    /// tiles are requested again once they come back.
+    pub(crate) fn clear(&mut self) {
+        self.cache.clear();
+        self.reported_failures.clear();
+        self.failures.clear();
+
+        if let Ok(downloads) = self.downloads.lock() {
+            for download in downloads.values() {
+                download.abort();
+            }
+        }
+
+        // Until they come back, they are not requested again.
+        for tile_id in self.in_flight.keys() {
+            self.stale.insert(*tile_id);
+            self.cache.put(*tile_id, None);
+        }
+    }
+
+    /// Cancel downloads of tiles which were not requested in the last pass the tiles were drawn
+    /// in, as they are not visible anymore, e.g. after zooming quickly through several levels.
+    /// They come back as [`Fetched::Retry`], so that they are requested again if they become
+    /// visible.
+    fn cancel_stale_downloads(&mut self) {
+        let Ok(downloads) = self.downloads.lock() else {
+            return;
+        };
+
+        for (tile_id, requested) in &self.in_flight {
+            let preloading = self
+                .preload
+                .as_ref()
+                .is_some_and(|preload| preload.in_flight.contains(tile_id));
+
+            if *requested < self.last_pass
+                && !preloading
+                && let Some(download) = downloads.get(tile_id)
+            {
+                log::trace!("Canceling download of {tile_id:?}, which is not visible anymore.");
+                download.abort();
+            }
+        }
+    }
+
+    /// Fetch given tiles in the background, with lower priority than the visible ones. Replaces
+    /// the previous preload, if any. `on_progress` is called each time a tile is done.
+    pub(crate) fn preload(
+        &mut self,
+        tile_ids: impl IntoIterator<Item = TileId>,
+        on_progress: impl FnMut(PreloadProgress) + Send + 'static,
+    ) {
+        let pending: VecDeque<_> = tile_ids.into_iter().collect();
+        self.preload = Some(Preload {
+            progress: PreloadProgress {
+                done: 0,
+                total: pending.len(),
             },
-        ) {
-            Ok(_) => {}
-            Err(err) if err.is_full() => {
-                // Trying to download too many tiles at once.
-                log::trace!("Request queue is full.");
+            pending,
+            in_flight: HashSet::new(),
+            on_progress: Box::new(on_progress),
+        });
+        self.send_queued(self.egui_ctx.cumulative_pass_nr());
+    }
+
+    /// Stop preloading. Tiles which are already being downloaded still land in the cache.
+    pub(crate) fn cancel_preload(&mut self) {
+        self.preload = None;
+    }
+
+    pub(crate) fn preload_progress(&self) -> Option<PreloadProgress> {
+        self.preload.as_ref().map(|preload| preload.progress)
+    }
+
+    /// Account for a preloaded tile being done, either loaded or failed for good.
+    fn preloaded(&mut self, tile_id: TileId) {
+        if let Some(preload) = &mut self.preload
+            && preload.in_flight.remove(&tile_id)
+        {
+            preload.done();
+        }
+        self.finish_preload_if_done();
+    }
+
+    fn finish_preload_if_done(&mut self) {
+        if self
+            .preload
+            .as_ref()
+            .is_some_and(|preload| preload.progress.done == preload.progress.total)
+        {
+            self.preload = None;
+        }
+    }
+
+    /// Tiles requested neither in this pass nor in the previous one are not visible anymore, so
+    /// they are dropped from the queue.
+    fn forget_stale_requests(&mut self, pass: u64) {
+        self.queue
+            .retain(|_, requested| is_recent(*requested, pass));
+    }
+
+    /// Pass queued requests to the IO thread, as long as it accepts them.
+    fn send_queued(&mut self, pass: u64) {
+        self.forget_stale_requests(pass);
+
+        let mut queued: Vec<_> = self.queue.iter().map(|(t, p)| (*t, *p)).collect();
+        queued.sort_by_key(|(_, pass)| Reverse(*pass));
+
+        for (tile_id, _) in queued {
+            match self.request_tx.try_send(tile_id) {
+                Ok(()) => {
+                    log::trace!("Requested tile: {tile_id:?}");
+                    self.queue.remove(&tile_id);
+                    self.in_flight.insert(tile_id, pass);
+                    self.cache.put(tile_id, None);
+                }
+                Err(err) if err.is_full() => {
+                    // Trying to download too many tiles at once.
+                    log::trace!("Request queue is full.");
+                    break;
+                }
+                Err(err) => {
+                    panic!("Failed to send tile request for {tile_id:?}: {err}");
+                }
+            }
+        }
+
+        // Remaining download slots, if any, are used for preloading.
+        if !self.queue.is_empty() {
+            return;
+        }
+
+        while let Some(preload) = &mut self.preload
+            && let Some(tile_id) = preload.pending.pop_front()
+        {
+            if self.cache.contains(&tile_id) {
+                // Already loaded, failed or being downloaded because it is visible.
+                preload.done();
+                continue;
             }
-            Err(err) => {
-                panic!("Failed to send tile request for {tile_id:?}: {err}");
+
+            match self.request_tx.try_send(tile_id) {
+                Ok(()) => {
+                    log::trace!("Requested tile for preloading: {tile_id:?}");
+                    preload.in_flight.insert(tile_id);
+                    self.in_flight.insert(tile_id, pass);
+                    self.cache.put(tile_id, None);
+                }
+                Err(err) if err.is_full() => {
+                    preload.pending.push_front(tile_id);
+                    break;
+                }
+                Err(err) => {
+                    panic!("Failed to send tile request for {tile_id:?}: {err}");
+                }
             }
         }
+
+        self.finish_preload_if_done();
+    }
+
+    pub(crate) fn take_failures(&mut self) -> Vec<TileFailure> {
+        std::mem::take(&mut self.failures)
+    }
+
+    /// Whether there are tiles waiting to be downloaded, being downloaded, or downloaded but not
+    /// put in the cache yet.
+    pub(crate) fn loading(&self) -> bool {
+        let pass = self.egui_ctx.cumulative_pass_nr();
+        self.queue
+            .values()
+            .any(|requested| is_recent(*requested, pass))
+            || !self.in_flight.is_empty()
     }
 
     pub(crate) fn stats(&self) -> Stats {
-        if let Ok(stats) = self.stats.lock() {
+        let stats = if let Ok(stats) = self.stats.lock() {
             stats.clone()
         } else {
             // I really do not want this to return a Result.
             Stats::default()
+        };
+
+        Stats {
+            queued: self.queue.len(),
+            ..stats
         }
     }
 }
 
+/// Tile which failed to load for good, e.g. because the server responded with an error status
+/// or the data could not be decoded.
+#[derive(Clone, Debug)]
+pub struct TileFailure {
+    pub tile_id: TileId,
+    /// Description of what went wrong.
+    pub error: String,
+}
+
+/// Whether a tile requested in the `requested` pass is still wanted in the current `pass`.
+fn is_recent(requested: u64, pass: u64) -> bool {
+    requested + 1 >= pass
+}
+
+/// Tiles to be fetched in the background.
+struct Preload {
+    /// Tiles not requested yet.
+    pending: VecDeque<TileId>,
+
+    /// Tiles passed to the IO thread, which did not come back yet.
+    in_flight: HashSet<TileId>,
+
+    progress: PreloadProgress,
+    on_progress: Box<dyn FnMut(PreloadProgress) + Send>,
+}
+
+impl Preload {
+    fn done(&mut self) {
+        self.progress.done += 1;
+        (self.on_progress)(self.progress);
+    }
+}
+
+/// How far preloading got, see [`crate::HttpTiles::preload`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PreloadProgress {
+    /// Number of tiles which were loaded, or failed for good.
+    pub done: usize,
+
+    /// Number of tiles to be preloaded in total.
+    pub total: usize,
+}
+
 #[derive(Clone, Default)]
 pub struct Stats {
     /// Number of tiles that are currently being downloaded.
     pub in_progress: usize,
+
+    /// Number of tiles waiting for a download slot.
+    pub queued: usize,
 }
@@ -2,6 +2,8 @@
 #![deny(clippy::unwrap_used, rustdoc::broken_intra_doc_links)]
 
 mod center;
+mod debug_tiles;
+mod fn_tiles;
 mod http_tiles;
 mod io;
 mod map;
@@ -31,24 +33,36 @@ mod pmtiles;
 mod position;
 mod projector;
 pub mod sources;
+#[cfg(test)]
+mod testing;
 mod tiles;
 mod zoom;
 
+pub use debug_tiles::DebugTiles;
+pub use fn_tiles::FnTiles;
 pub use http_tiles::HttpTiles;
-pub use io::tiles_io::Stats;
-pub use io::{HeaderValue, MaxParallelDownloads, http::HttpOptions};
+pub use io::tiles_io::{PreloadProgress, Stats, TileFailure};
+pub use io::{
+    FetchError, FetchFuture, HeaderMap, HeaderValue, MaxParallelDownloads, RateLimited,
+    RequestHeaders, TileFetcher, http::HttpOptions,
+};
 pub use map::Map;
-pub use memory::MapMemory;
+pub use memory::{Interaction, MapMemory};
 pub use options::Options;
 pub use plugin::Plugin;
 #[cfg(feature = "pmtiles")]
 pub use pmtiles::PmTiles;
-pub use position::{Position, lat_lon, lon_lat};
-pub use projector::{MercatorProjection, ProjectedProjection, Projection, ScreenProjector};
+pub use position::{
+    Position, bearing_degrees, destination, distance_meters, format_decimal, format_dms, lat_lon,
+    lon_lat,
+};
+pub use projector::{
+    MercatorProjection, PlateCarreeProjection, ProjectedProjection, Projection, ScreenProjector,
+};
 pub use style::Style;
 #[cfg(feature = "mvt")]
 pub use style::{Color, Filter, Float, Layer, Paint, Value, json};
-pub use tiles::{Tile, TileId, TilePiece, Tiles};
+pub use tiles::{Tile, TileId, TilePiece, Tiles, visible_tiles};
 pub use zoom::InvalidZoom;
 
 // TODO: In future, I'd like to expose full drawing API instead of this.
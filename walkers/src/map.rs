@@ -1,15 +1,25 @@
 use egui::{
-    DragPanButtons, InnerResponse, PointerButton, Response, Sense, Ui, UiBuilder, Vec2, Widget,
+    Color32, DragPanButtons, EventFilter, InnerResponse, Key, PointerButton, Response, Sense, Ui,
+    UiBuilder, Vec2, Widget,
 };
+use std::time::Duration;
 
 use crate::{
     MapMemory, Options, Plugin, Position, Tiles,
     center::Center,
+    memory::Interaction,
     position::AdjustedPosition,
     projector::{Projection, ScreenProjector},
+    sources::Attribution,
     tiles::draw_tiles,
 };
 
+/// How far the map moves when an arrow key is pressed, in pixels.
+const KEYBOARD_PAN_STEP: f32 = 64.;
+
+/// How long it takes to snap to an integer zoom level after a zoom gesture.
+const ZOOM_SNAP_DURATION: Duration = Duration::from_millis(150);
+
 struct Layer<'a, P> {
     tiles: &'a mut dyn Tiles<Projection = P>,
     transparency: f32,
@@ -34,12 +44,17 @@ struct Layer<'a, P> {
 /// Initially, the map follows `my_position` argument which is typically fed by a GPS sensor or
 /// other geo-localization method. If user drags the map, it enters a "detached state". You can use
 /// [`MapMemory`]'s methods to change the state programmatically.
+///
+/// The map's [`Response`] reports clicks as usual, and long presses on touch screens via
+/// [`Response::long_touched`], which can be turned into a [`Position`] with
+/// [`ScreenProjector::unproject`] applied to [`Response::interact_pointer_pos`].
 pub struct Map<'a, 'b, 'c, P: Projection + 'static> {
     projection: P,
     layers: Vec<Layer<'b, P>>,
     memory: &'a mut MapMemory,
     my_position: Position,
     plugins: Vec<Box<dyn Plugin + 'c>>,
+    underlay_plugins: Vec<Box<dyn Plugin + 'c>>,
     options: Options,
 }
 
@@ -51,6 +66,7 @@ impl<'a, 'b, 'c, P: Projection + 'static> Map<'a, 'b, 'c, P> {
             memory,
             my_position,
             plugins: Vec::default(),
+            underlay_plugins: Vec::default(),
             options: Options::default(),
         }
     }
@@ -61,8 +77,19 @@ impl<'a, 'b, 'c, P: Projection + 'static> Map<'a, 'b, 'c, P> {
         self
     }
 
+    /// Add plugin which runs before the tiles are drawn, so that whatever it paints ends up
+    /// underneath them, e.g. a background or a graticule shining through a transparent layer.
+    pub fn with_underlay_plugin(mut self, plugin: impl Plugin + 'c) -> Self {
+        self.underlay_plugins.push(Box::new(plugin));
+        self
+    }
+
     /// Add a tile layer. All layers are drawn on top of each other with given transparency.
     ///
+    /// Transparency multiplies the alpha of the tiles, so 1.0 draws them fully opaque and e.g.
+    /// 0.5 lets the background or layers underneath shine through. It applies to a single layer
+    /// as well, e.g. to dim a base map under a heavy data overlay.
+    ///
     /// The tile source must use the same projection as the map, enforced at compile time
     /// via the [`Tiles::Projection`] associated type.
     pub fn with_layer(
@@ -81,6 +108,22 @@ impl<'a, 'b, 'c, P: Projection + 'static> Map<'a, 'b, 'c, P> {
         &self.projection
     }
 
+    /// Attributions of all layers added so far, bottom one first. They can be passed e.g. to an
+    /// attribution overlay plugin.
+    pub fn attributions(&self) -> Vec<Attribution> {
+        self.layers
+            .iter()
+            .map(|layer| layer.tiles.attribution())
+            .collect()
+    }
+
+    /// Fill the map with given color before drawing anything else, so that areas without tiles,
+    /// e.g. while they are loading, are not transparent. By default, nothing is painted there.
+    pub fn with_background(mut self, color: Color32) -> Self {
+        self.options.background = Some(color);
+        self
+    }
+
     /// Set whether map should perform zoom gesture.
     ///
     /// Zoom is typically triggered by the mouse wheel while holding <kbd>ctrl</kbd> key on native
@@ -90,7 +133,9 @@ impl<'a, 'b, 'c, P: Projection + 'static> Map<'a, 'b, 'c, P> {
         self
     }
 
-    /// Specify which pointer buttons can be used to pan by clicking and dragging.
+    /// Specify which pointer buttons can be used to pan by clicking and dragging, e.g.
+    /// [`DragPanButtons::MIDDLE`] to leave the primary one for the application. Only drags with
+    /// these buttons make the map glide after releasing. Default is [`DragPanButtons::PRIMARY`].
     pub fn drag_pan_buttons(mut self, buttons: DragPanButtons) -> Self {
         self.options.drag_pan_buttons = buttons;
         self
@@ -103,6 +148,14 @@ impl<'a, 'b, 'c, P: Projection + 'static> Map<'a, 'b, 'c, P> {
         self
     }
 
+    /// Change how fast scrolling zooms when [`Map::zoom_with_ctrl`] is disabled, independently of
+    /// [`Map::zoom_speed`] which also applies to pinch and <kbd>ctrl</kbd> + scroll.
+    /// Default value is 0.25
+    pub fn scroll_zoom_sensitivity(mut self, sensitivity: f64) -> Self {
+        self.options.scroll_zoom_sensitivity = sensitivity;
+        self
+    }
+
     /// Set whether to enable double click primary mouse button to zoom
     pub fn double_click_to_zoom(mut self, enabled: bool) -> Self {
         self.options.double_click_to_zoom = enabled;
@@ -115,6 +168,15 @@ impl<'a, 'b, 'c, P: Projection + 'static> Map<'a, 'b, 'c, P> {
         self
     }
 
+    /// Set whether triple click, or triple tap on touch screens, makes the map follow
+    /// `my_position` again after it was dragged away, see [`MapMemory::follow_my_position`].
+    /// Useful where there is no room for a dedicated button. When combined with
+    /// [`Map::double_click_to_zoom`], the second click zooms in too. Disabled by default.
+    pub fn triple_click_to_follow_my_position(mut self, enabled: bool) -> Self {
+        self.options.triple_click_to_follow_my_position = enabled;
+        self
+    }
+
     /// Sets the zoom behaviour
     ///
     /// When enabled zoom is done with mouse wheel while holding <kbd>ctrl</kbd> key on native
@@ -137,6 +199,22 @@ impl<'a, 'b, 'c, P: Projection + 'static> Map<'a, 'b, 'c, P> {
         self
     }
 
+    /// Set whether the zoom level should snap to the nearest integer once a zoom gesture ends,
+    /// so that raster tiles are drawn at their native resolution, without blur. During the
+    /// gesture, zoom still changes continuously. Disabled by default.
+    pub fn zoom_snap(mut self, enabled: bool) -> Self {
+        self.options.zoom_snap = enabled;
+        self
+    }
+
+    /// Set whether the map keeps gliding for a while after being dragged and released. Turning
+    /// it off makes the map stop right away, e.g. for users sensitive to motion. Enabled by
+    /// default.
+    pub fn inertia(mut self, enabled: bool) -> Self {
+        self.options.inertia = enabled;
+        self
+    }
+
     /// Set the threshold for pulling the map back to `my_position` when dragged.
     ///
     /// It can be used to prevent the map from being accidentally detached when the user clicks on
@@ -146,6 +224,33 @@ impl<'a, 'b, 'c, P: Projection + 'static> Map<'a, 'b, 'c, P> {
         self
     }
 
+    /// Smooth out jitter of `my_position`, e.g. caused by noise of a GPS sensor, so that the map
+    /// following it does not shake. The position drawn follows the given one with a delay of
+    /// roughly `time_constant`, e.g. 500 ms. Disabled by default.
+    ///
+    /// Only the map's center and [`ScreenProjector`] are affected, the `my_position` given to
+    /// [`Map::new`] is still the raw one.
+    pub fn smooth_my_position(mut self, time_constant: Duration) -> Self {
+        self.options.my_position_smoothing = Some(time_constant);
+        self
+    }
+
+    /// Draw this many extra rings of tiles around the visible area, so that there are no blank
+    /// edges while panning fast, e.g. on touch screens. Tiles in the margin are downloaded like
+    /// the visible ones. Default is 0, which covers just the visible area.
+    pub fn tile_overscan(mut self, tiles: u32) -> Self {
+        self.options.tile_overscan = tiles;
+        self
+    }
+
+    /// Set whether map should be controlled by keyboard: arrow keys to pan and <kbd>+</kbd> and
+    /// <kbd>-</kbd> to zoom. Keys are handled only when the map widget has focus, which it gains
+    /// when clicked or navigated to with <kbd>tab</kbd>. Disabled by default.
+    pub fn keyboard_gestures(mut self, enabled: bool) -> Self {
+        self.options.keyboard_gestures = enabled;
+        self
+    }
+
     /// Show the map widget inside a [`egui::Ui`].
     pub fn show<R>(
         mut self,
@@ -155,14 +260,39 @@ impl<'a, 'b, 'c, P: Projection + 'static> Map<'a, 'b, 'c, P> {
         let (rect, mut response) =
             ui.allocate_exact_size(ui.available_size(), Sense::click_and_drag());
 
-        let mut changed = self.handle_gestures(ui, &response);
         let delta_time = ui.input(|reader| reader.stable_dt);
+
+        if let Some(time_constant) = self.options.my_position_smoothing {
+            let (smoothed, settled) = self.memory.smooth_my_position(
+                &self.projection,
+                self.my_position,
+                time_constant.as_secs_f32(),
+                delta_time,
+            );
+            self.my_position = smoothed;
+            if !settled {
+                ui.request_repaint();
+            }
+        }
+
+        self.memory.apply_pending_pan(self.my_position);
+        self.memory
+            .apply_pending_recenter(&self.projection, self.my_position);
+        let was_moving = self.memory.center_mode.moving();
+
+        let mut changed = self.handle_gestures(ui, &response, delta_time);
+        changed |= self.animate_zoom(&response, delta_time);
         let zoom = self.memory.zoom;
         changed |= self
             .memory
             .center_mode
             .update_movement(delta_time, zoom.into());
 
+        if was_moving && !self.memory.center_mode.moving() {
+            self.memory.last_interaction = Some(Interaction::Settled);
+        }
+        self.memory.clamp_to_poles(&self.projection, rect.height());
+
         if changed {
             response.mark_changed();
             ui.request_repaint();
@@ -173,15 +303,39 @@ impl<'a, 'b, 'c, P: Projection + 'static> Map<'a, 'b, 'c, P> {
             .center_mode
             .position(self.my_position, &self.projection);
         let painter = ui.painter().with_clip_rect(rect);
+        if let Some(background) = self.options.background {
+            painter.rect_filled(rect, 0., background);
+        }
+
+        let projection: &dyn Projection = &self.projection;
+        let tile_size = self
+            .layers
+            .first()
+            .map_or(256, |layer| layer.tiles.tile_size());
+        let projector =
+            ScreenProjector::new(projection, response.rect, self.memory, self.my_position)
+                .with_tile_size(tile_size);
+
+        // Run underlay plugins.
+        for (idx, plugin) in self.underlay_plugins.into_iter().enumerate() {
+            let mut child_ui =
+                ui.new_child(UiBuilder::new().max_rect(rect).id_salt(("underlay", idx)));
+            plugin.run(&mut child_ui, &response, &projector);
+        }
 
         for layer in self.layers {
-            draw_tiles(&painter, map_center, zoom, layer.tiles, layer.transparency);
+            draw_tiles(
+                &painter,
+                &self.projection,
+                map_center,
+                zoom,
+                layer.tiles,
+                layer.transparency,
+                self.options.tile_overscan,
+            );
         }
 
         // Run plugins.
-        let projection: &dyn Projection = &self.projection;
-        let projector =
-            ScreenProjector::new(projection, response.rect, self.memory, self.my_position);
         for (idx, plugin) in self.plugins.into_iter().enumerate() {
             let mut child_ui = ui.new_child(UiBuilder::new().max_rect(rect).id_salt(idx));
             plugin.run(&mut child_ui, &response, &projector);
@@ -196,55 +350,57 @@ impl<'a, 'b, 'c, P: Projection + 'static> Map<'a, 'b, 'c, P> {
 
 impl<P: Projection + 'static> Map<'_, '_, '_, P> {
     /// Handle user inputs and recalculate everything accordingly. Returns whether something changed.
-    fn handle_gestures(&mut self, ui: &mut Ui, response: &Response) -> bool {
+    fn handle_gestures(&mut self, ui: &mut Ui, response: &Response, delta_time: f32) -> bool {
         let zoom_delta = self.zoom_delta(ui, response);
 
         // Zooming and dragging need to be exclusive, otherwise the map will get dragged when
         // pinch gesture is used.
-        let changed = if (zoom_delta - 1.0).abs() > 0.001
+        let mut changed = if (zoom_delta - 1.0).abs() > 0.001
             && ui.ui_contains_pointer()
             && self.options.zoom_gesture_enabled
         {
-            // Displacement of mouse pointer relative to widget center
-            let offset = input_offset(ui, response);
-
-            // While zooming, we want to keep the location under the mouse pointer fixed on the
-            // screen. To achieve this, we first move the location to the widget's center,
-            // then adjust zoom level, finally move the location back to the original screen
-            // position.
-            if let Some(offset) = offset {
-                // If map is tracking `my_position` and the input offset is close, just let it be.
-                if self.memory.detached(&self.projection).is_some()
-                    || offset.length() > self.options.pull_to_my_position_threshold
-                {
-                    self.memory.center_mode = Center::Exact(
-                        AdjustedPosition::new(self.position()).shift(-offset, self.memory.zoom()),
-                    );
-                }
-            }
+            // User takes over, so the animation would only fight with the gesture.
+            self.memory.zoom_animation = None;
 
             // Shift by 1 because of the values given by zoom_delta(). Multiple by zoom_speed(defaults to 2.0),
             // because then it felt right with both mouse wheel, and an Android phone.
-            self.memory
-                .zoom
-                .zoom_by((zoom_delta - 1.) * self.options.zoom_speed);
-
-            if let Some(offset) = offset {
-                self.memory.center_mode = self
-                    .memory
-                    .center_mode
-                    .clone()
-                    .shift(offset, self.memory.zoom());
-            }
+            let offset = input_offset(ui, response);
+            self.zoom_at((zoom_delta - 1.) * self.options.zoom_speed, offset);
+            self.memory.last_interaction = Some(Interaction::Zoomed);
+            self.memory.zoom_gesture_focus =
+                Some(response.rect.center() + offset.unwrap_or_default());
 
             true
         } else {
-            self.memory.center_mode.handle_gestures(
+            let changed = self.memory.center_mode.handle_gestures(
                 response,
                 self.my_position,
                 self.options.pull_to_my_position_threshold,
                 self.options.drag_pan_buttons,
-            )
+                self.options.inertia,
+                delta_time,
+            );
+
+            if let Center::Moving { direction, .. } = self.memory.center_mode
+                && direction != Vec2::ZERO
+            {
+                self.memory.last_interaction = Some(Interaction::Panned);
+            }
+
+            // Fingers may rest for a moment during a pinch, which does not end the gesture.
+            if !ui.input(|input| input.any_touches())
+                && let Some(focus) = self.memory.zoom_gesture_focus.take()
+                && self.options.zoom_snap
+            {
+                // Rounded zoom is valid, as the current one is.
+                let _ = self.memory.set_zoom_animated(
+                    self.memory.zoom().round(),
+                    ZOOM_SNAP_DURATION,
+                    Some(focus),
+                );
+            }
+
+            changed
         };
 
         // Only enable panning with mouse_wheel if we are zooming with ctrl. But always allow touch devices to pan
@@ -258,12 +414,129 @@ impl<P: Projection + 'static> Map<'_, '_, '_, P> {
                 self.memory.center_mode = Center::Exact(
                     AdjustedPosition::new(self.position()).shift(scroll_delta, self.memory.zoom()),
                 );
+                self.memory.last_interaction = Some(Interaction::Panned);
             }
         }
 
+        if self.options.triple_click_to_follow_my_position
+            && ui.ui_contains_pointer()
+            && response.triple_clicked()
+            && self.memory.detached(&self.projection).is_some()
+        {
+            self.memory.follow_my_position();
+            self.memory.last_interaction = Some(Interaction::Panned);
+            changed = true;
+        }
+
+        if self.options.keyboard_gestures {
+            changed |= self.handle_keyboard(ui, response);
+        }
+
         changed
     }
 
+    /// Pan and zoom using keyboard, if the map has focus. Returns whether something changed.
+    fn handle_keyboard(&mut self, ui: &mut Ui, response: &Response) -> bool {
+        if response.clicked() || response.drag_started() {
+            response.request_focus();
+        }
+
+        if !response.has_focus() {
+            return false;
+        }
+
+        // Keep the focus on the map when arrow keys are used, instead of moving it to other
+        // widgets.
+        ui.memory_mut(|memory| {
+            memory.set_focus_lock_filter(
+                response.id,
+                EventFilter {
+                    horizontal_arrows: true,
+                    vertical_arrows: true,
+                    ..Default::default()
+                },
+            );
+        });
+
+        let (offset, zoom) = ui.input(|input| {
+            let mut offset = Vec2::ZERO;
+            for (key, direction) in [
+                (Key::ArrowLeft, Vec2::RIGHT),
+                (Key::ArrowRight, Vec2::LEFT),
+                (Key::ArrowUp, Vec2::DOWN),
+                (Key::ArrowDown, Vec2::UP),
+            ] {
+                if input.key_pressed(key) {
+                    offset += direction * KEYBOARD_PAN_STEP;
+                }
+            }
+
+            let mut zoom = 0.;
+            if input.key_pressed(Key::Plus) || input.key_pressed(Key::Equals) {
+                zoom += 1.;
+            }
+            if input.key_pressed(Key::Minus) {
+                zoom -= 1.;
+            }
+
+            (offset, zoom)
+        });
+
+        if offset != Vec2::ZERO {
+            self.memory.center_mode = Center::Exact(
+                AdjustedPosition::new(self.position()).shift(offset, self.memory.zoom()),
+            );
+            self.memory.last_interaction = Some(Interaction::Panned);
+        }
+
+        if zoom != 0. {
+            // Otherwise, the animation would undo the change right away.
+            self.memory.zoom_animation = None;
+            self.memory.zoom.zoom_by(zoom);
+            self.memory.last_interaction = Some(Interaction::Zoomed);
+        }
+
+        offset != Vec2::ZERO || zoom != 0.
+    }
+
+    /// Change zoom by `delta` levels, keeping the location at `offset` from the widget's center
+    /// fixed on the screen.
+    fn zoom_at(&mut self, delta: f64, offset: Option<Vec2>) {
+        // To keep the location fixed, we first move it to the widget's center, then adjust zoom
+        // level, finally move the location back to the original screen position.
+        if let Some(offset) = offset {
+            // If map is tracking `my_position` and the input offset is close, just let it be.
+            if self.memory.detached(&self.projection).is_some()
+                || offset.length() > self.options.pull_to_my_position_threshold
+            {
+                self.memory.center_mode = Center::Exact(
+                    AdjustedPosition::new(self.position()).shift(-offset, self.memory.zoom()),
+                );
+            }
+        }
+
+        self.memory.zoom.zoom_by(delta);
+
+        if let Some(offset) = offset {
+            self.memory.center_mode = self
+                .memory
+                .center_mode
+                .clone()
+                .shift(offset, self.memory.zoom());
+        }
+    }
+
+    /// Progress the animation started by [`MapMemory::set_zoom_animated`]. Returns whether
+    /// something changed.
+    fn animate_zoom(&mut self, response: &Response, delta_time: f32) -> bool {
+        if let Some((delta, focus)) = self.memory.advance_zoom_animation(delta_time) {
+            self.zoom_at(delta, focus.map(|focus| focus - response.rect.center()));
+            true
+        } else {
+            false
+        }
+    }
+
     /// Calculate the zoom delta based on the input.
     fn zoom_delta(&self, ui: &mut Ui, response: &Response) -> f64 {
         let mut zoom_delta = ui.input(|input| input.zoom_delta()) as f64;
@@ -293,7 +566,7 @@ impl<P: Projection + 'static> Map<'_, '_, '_, P> {
                             .stable_dt
                             .clamp(input.predicted_dt * 0.5, input.predicted_dt * 2.0)
                 }) as f64
-                    / 4.0;
+                    * self.options.scroll_zoom_sensitivity;
         };
 
         zoom_delta
@@ -320,8 +593,710 @@ fn input_offset(ui: &mut Ui, response: &Response) -> Option<Vec2> {
         .input(|input| input.multi_touch())
         .map(|multi_touch| multi_touch.center_pos);
 
-    // On touch we get both, so make touch the priority.
+    // On touch we get both, so make touch the priority. When a finger is lifted, e.g. at the end
+    // of a double tap, there is no hover position anymore, but the tap's position is still known.
     touch_offset
         .or(mouse_offset)
+        .or(response.interact_pointer_pos())
         .map(|pos| pos - response.rect.center())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MercatorProjection, lon_lat, testing::MockTiles};
+    use egui::{Event, FullOutput, Id, Modifiers, Pos2, RawInput, Rect, pos2};
+
+    /// Screen the maps in tests are shown on.
+    fn screen() -> Rect {
+        Rect::from_min_size(Pos2::ZERO, Vec2::new(400., 300.))
+    }
+
+    /// Run a frame with given input events, in which `add_contents` shows the map.
+    fn run_ui_frame(
+        ctx: &egui::Context,
+        time: f64,
+        events: Vec<Event>,
+        add_contents: impl FnOnce(&mut Ui),
+    ) -> FullOutput {
+        ctx.begin_pass(RawInput {
+            screen_rect: Some(screen()),
+            time: Some(time),
+            events,
+            ..Default::default()
+        });
+        add_contents(&mut Ui::new(ctx.clone(), Id::new("map"), UiBuilder::new()));
+        ctx.end_pass()
+    }
+
+    /// Run a frame of a map with given input events, returning the map's rect. The map is set up
+    /// by `configure`, e.g. with options or layers.
+    fn run_frame<'a, 'b, 'c>(
+        ctx: &egui::Context,
+        time: f64,
+        events: Vec<Event>,
+        memory: &'a mut MapMemory,
+        my_position: Position,
+        configure: impl FnOnce(
+            Map<'a, 'b, 'c, MercatorProjection>,
+        ) -> Map<'a, 'b, 'c, MercatorProjection>,
+    ) -> Rect {
+        let mut rect = Rect::NOTHING;
+        let _ = run_ui_frame(ctx, time, events, |ui| {
+            rect = ui
+                .add(configure(Map::new(MercatorProjection, memory, my_position)))
+                .rect;
+        });
+        rect
+    }
+
+    #[test]
+    fn animated_zoom_keeps_the_focus_in_place() {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::default();
+        memory.center_at(my_position);
+        let focus = pos2(100., 250.);
+
+        let rect = run_frame(&ctx, 0., Vec::new(), &mut memory, my_position, |map| map);
+        let under_focus =
+            ScreenProjector::new(&MercatorProjection, rect, &memory, my_position).unproject(focus);
+
+        memory
+            .set_zoom_animated(14., std::time::Duration::from_millis(200), Some(focus))
+            .expect("valid zoom");
+
+        for n in 1..=20 {
+            run_frame(
+                &ctx,
+                n as f64 / 60.,
+                Vec::new(),
+                &mut memory,
+                my_position,
+                |map| map,
+            );
+        }
+
+        assert!(!memory.animating());
+        assert!((memory.zoom() - 14.).abs() < 1e-9);
+        let projector = ScreenProjector::new(&MercatorProjection, rect, &memory, my_position);
+        let moved = projector.project(under_focus) - focus;
+        assert!(moved.length() < 0.5, "{moved:?}");
+    }
+
+    #[test]
+    fn keyboard_zoom_interrupts_animated_zoom() {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::new(my_position, 12.).expect("valid zoom");
+        let middle = pos2(200., 150.);
+        let plus = Event::Key {
+            key: Key::Plus,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: Modifiers::NONE,
+        };
+
+        // Click the map to give it the keyboard focus.
+        let frames = [
+            vec![Event::PointerMoved(middle)],
+            vec![click(middle, true)],
+            vec![click(middle, false)],
+        ];
+        for (n, events) in frames.into_iter().enumerate() {
+            run_frame(
+                &ctx,
+                n as f64 / 60.,
+                events,
+                &mut memory,
+                my_position,
+                |map| map.keyboard_gestures(true),
+            );
+        }
+
+        memory
+            .set_zoom_animated(14., std::time::Duration::from_millis(200), None)
+            .expect("valid zoom");
+
+        let mut before_key = memory.zoom();
+        for n in 3..30 {
+            let events = if n == 5 {
+                before_key = memory.zoom();
+                vec![plus.clone()]
+            } else {
+                vec![]
+            };
+            run_frame(
+                &ctx,
+                n as f64 / 60.,
+                events,
+                &mut memory,
+                my_position,
+                |map| map.keyboard_gestures(true),
+            );
+        }
+
+        assert!(before_key > 12. && before_key < 13., "{before_key}");
+        assert!(
+            (memory.zoom() - (before_key + 1.)).abs() < 1e-9,
+            "{}",
+            memory.zoom()
+        );
+        assert!(!memory.animating());
+    }
+
+    fn click(pos: Pos2, pressed: bool) -> Event {
+        Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn double_click_zooms_towards_the_cursor() {
+        double_click(false);
+    }
+
+    #[test]
+    fn double_tap_zooms_towards_the_finger() {
+        // Pointer is gone right after the finger is lifted.
+        double_click(true);
+    }
+
+    fn double_click(touch: bool) {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::default();
+        memory.center_at(my_position);
+        let cursor = pos2(300., 100.);
+
+        let rect = run_frame(
+            &ctx,
+            0.,
+            vec![Event::PointerMoved(cursor)],
+            &mut memory,
+            my_position,
+            |map| map.double_click_to_zoom(true),
+        );
+        let under_cursor =
+            ScreenProjector::new(&MercatorProjection, rect, &memory, my_position).unproject(cursor);
+        let zoom = memory.zoom();
+
+        for (n, pressed) in [true, false, true, false].into_iter().enumerate() {
+            let mut events = vec![click(cursor, pressed)];
+            if touch && !pressed {
+                events.push(Event::PointerGone);
+            }
+
+            run_frame(
+                &ctx,
+                0.05 * (n + 1) as f64,
+                events,
+                &mut memory,
+                my_position,
+                |map| map.double_click_to_zoom(true),
+            );
+        }
+
+        assert!(memory.zoom() > zoom);
+        let projector = ScreenProjector::new(&MercatorProjection, rect, &memory, my_position);
+        let moved = projector.project(under_cursor) - cursor;
+        assert!(moved.length() < 0.5, "{moved:?}");
+    }
+
+    #[test]
+    fn triple_click_follows_my_position() {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::default();
+        memory.center_at(lon_lat(18., 52.));
+        let cursor = pos2(300., 100.);
+
+        let frame = |n: usize, events: Vec<Event>, memory: &mut MapMemory| {
+            run_frame(&ctx, 0.05 * n as f64, events, memory, my_position, |map| {
+                map.triple_click_to_follow_my_position(true)
+            });
+        };
+
+        frame(0, vec![Event::PointerMoved(cursor)], &mut memory);
+        for (n, pressed) in [true, false, true, false].into_iter().enumerate() {
+            frame(n + 1, vec![click(cursor, pressed)], &mut memory);
+        }
+        assert!(memory.detached(&MercatorProjection).is_some());
+
+        for (n, pressed) in [true, false].into_iter().enumerate() {
+            frame(n + 5, vec![click(cursor, pressed)], &mut memory);
+        }
+        assert!(memory.detached(&MercatorProjection).is_none());
+        assert_eq!(memory.take_last_interaction(), Some(Interaction::Panned));
+    }
+
+    #[test]
+    fn layers_with_different_tile_sizes_cover_the_same_area() {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::default();
+        let mut small = MockTiles::new(256);
+        let mut large = MockTiles::new(512);
+
+        run_frame(&ctx, 0., Vec::new(), &mut memory, my_position, |map| {
+            map.with_layer(&mut small, 1.).with_layer(&mut large, 0.5)
+        });
+
+        // Each 512px tile bundles four 256px tiles of the next zoom level.
+        let parents: std::collections::BTreeSet<_> = small
+            .requested()
+            .iter()
+            .map(|(zoom, x, y)| (zoom - 1, x / 2, y / 2))
+            .collect();
+        assert!(!parents.is_empty());
+        assert_eq!(parents, large.requested());
+    }
+
+    #[test]
+    fn small_tiles_are_taken_from_a_higher_zoom_level() {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::default();
+        let mut normal = MockTiles::new(256);
+        let mut small = MockTiles::new(128);
+
+        run_frame(&ctx, 0., Vec::new(), &mut memory, my_position, |map| {
+            map.with_layer(&mut normal, 1.).with_layer(&mut small, 0.5)
+        });
+
+        // Four 128px tiles of the next zoom level make up a 256px tile.
+        let parents: std::collections::BTreeSet<_> = small
+            .requested()
+            .iter()
+            .map(|(zoom, x, y)| (zoom - 1, x / 2, y / 2))
+            .collect();
+        assert!(!parents.is_empty());
+        assert_eq!(parents, normal.requested());
+        assert!(
+            small
+                .requested
+                .iter()
+                .all(|tile_id| tile_id.zoom == memory.zoom_level() + 1)
+        );
+    }
+
+    #[test]
+    fn attributions_of_all_layers() {
+        let mut memory = MapMemory::default();
+        let mut small = MockTiles::new(256).with_attribution("small");
+        let mut large = MockTiles::new(512).with_attribution("large");
+
+        let map = Map::new(MercatorProjection, &mut memory, lon_lat(17., 51.))
+            .with_layer(&mut small, 1.)
+            .with_layer(&mut large, 0.5);
+
+        let texts: Vec<_> = map.attributions().iter().map(|a| a.text).collect();
+        assert_eq!(texts, ["small", "large"]);
+    }
+
+    struct Logging<'a>(&'a std::cell::RefCell<Vec<&'static str>>, &'static str);
+
+    impl Plugin for Logging<'_> {
+        fn run(self: Box<Self>, _ui: &mut Ui, _response: &Response, _projector: &ScreenProjector) {
+            self.0.borrow_mut().push(self.1);
+        }
+    }
+
+    impl Tiles for Logging<'_> {
+        type Projection = MercatorProjection;
+
+        fn at(&mut self, _tile_id: crate::TileId) -> Option<crate::TilePiece> {
+            let mut log = self.0.borrow_mut();
+            if log.last() != Some(&self.1) {
+                log.push(self.1);
+            }
+            None
+        }
+
+        fn attribution(&self) -> Attribution {
+            Attribution {
+                text: "",
+                url: "",
+                logo_light: None,
+                logo_dark: None,
+            }
+        }
+
+        fn tile_size(&self) -> u32 {
+            256
+        }
+    }
+
+    #[test]
+    fn underlay_plugins_run_before_tiles_are_drawn() {
+        let ctx = egui::Context::default();
+        let log = std::cell::RefCell::new(Vec::new());
+        let mut memory = MapMemory::default();
+        let mut tiles = Logging(&log, "tiles");
+
+        run_frame(
+            &ctx,
+            0.,
+            Vec::new(),
+            &mut memory,
+            lon_lat(17., 51.),
+            |map| {
+                map.with_layer(&mut tiles, 1.)
+                    .with_plugin(Logging(&log, "overlay"))
+                    .with_underlay_plugin(Logging(&log, "underlay"))
+            },
+        );
+
+        assert_eq!(*log.borrow(), ["underlay", "tiles", "overlay"]);
+    }
+
+    /// Plugin claiming clicks and drags in the middle of the map.
+    struct Claim;
+
+    impl Plugin for Claim {
+        fn run(self: Box<Self>, ui: &mut Ui, response: &Response, _projector: &ScreenProjector) {
+            let rect = Rect::from_center_size(response.rect.center(), Vec2::splat(100.));
+            ui.interact(rect, ui.id().with("claim"), Sense::click_and_drag());
+        }
+    }
+
+    /// Drag from the middle of the map, returning whether it got detached.
+    fn drag_from_the_middle(claim: bool) -> bool {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::default();
+        let middle = pos2(200., 150.);
+
+        let frames = [
+            vec![Event::PointerMoved(middle)],
+            vec![click(middle, true)],
+            vec![Event::PointerMoved(middle + Vec2::new(50., 0.))],
+            vec![Event::PointerMoved(middle + Vec2::new(100., 0.))],
+            vec![click(middle + Vec2::new(100., 0.), false)],
+        ];
+
+        for (n, events) in frames.into_iter().enumerate() {
+            run_frame(
+                &ctx,
+                n as f64 / 60.,
+                events,
+                &mut memory,
+                my_position,
+                |map| if claim { map.with_plugin(Claim) } else { map },
+            );
+        }
+
+        memory.detached(&MercatorProjection).is_some()
+    }
+
+    #[test]
+    fn plugins_can_claim_the_pointer() {
+        assert!(drag_from_the_middle(false));
+        assert!(!drag_from_the_middle(true));
+    }
+
+    /// Drag with given button on a map panned with given buttons, returning whether it got
+    /// detached.
+    fn drag_with(button: PointerButton, pan_buttons: DragPanButtons) -> bool {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::default();
+        let middle = pos2(200., 150.);
+        let press = |pos, pressed| Event::PointerButton {
+            pos,
+            button,
+            pressed,
+            modifiers: Modifiers::NONE,
+        };
+
+        let frames = [
+            vec![Event::PointerMoved(middle)],
+            vec![press(middle, true)],
+            vec![Event::PointerMoved(middle + Vec2::new(50., 0.))],
+            vec![Event::PointerMoved(middle + Vec2::new(100., 0.))],
+            vec![press(middle + Vec2::new(100., 0.), false)],
+            vec![],
+        ];
+
+        for (n, events) in frames.into_iter().enumerate() {
+            run_frame(
+                &ctx,
+                n as f64 / 60.,
+                events,
+                &mut memory,
+                my_position,
+                |map| map.drag_pan_buttons(pan_buttons),
+            );
+        }
+
+        memory.detached(&MercatorProjection).is_some()
+    }
+
+    #[test]
+    fn panning_with_configured_button() {
+        assert!(drag_with(PointerButton::Primary, DragPanButtons::PRIMARY));
+        assert!(!drag_with(PointerButton::Middle, DragPanButtons::PRIMARY));
+
+        assert!(drag_with(PointerButton::Middle, DragPanButtons::MIDDLE));
+        assert!(!drag_with(PointerButton::Primary, DragPanButtons::MIDDLE));
+    }
+
+    /// Scroll over the map without holding ctrl, returning how much the zoom changed.
+    fn scroll_zoom(sensitivity: Option<f64>) -> f64 {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::default();
+        let middle = pos2(200., 150.);
+
+        for n in 0..10 {
+            let mut events = vec![Event::PointerMoved(middle)];
+            if n == 1 {
+                events.push(Event::MouseWheel {
+                    unit: egui::MouseWheelUnit::Point,
+                    delta: Vec2::new(0., 10.),
+                    phase: egui::TouchPhase::Move,
+                    modifiers: Modifiers::NONE,
+                });
+            }
+
+            run_frame(
+                &ctx,
+                n as f64 / 60.,
+                events,
+                &mut memory,
+                my_position,
+                |map| {
+                    let map = map.zoom_with_ctrl(false);
+                    match sensitivity {
+                        Some(sensitivity) => map.scroll_zoom_sensitivity(sensitivity),
+                        None => map,
+                    }
+                },
+            );
+        }
+
+        memory.zoom() - MapMemory::default().zoom()
+    }
+
+    #[test]
+    fn zoom_snaps_to_integer_level_after_scrolling() {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::default();
+        let pointer = pos2(300., 100.);
+        let under_pointer = |memory: &MapMemory| {
+            ScreenProjector::new(&MercatorProjection, screen(), memory, my_position)
+                .unproject(pointer)
+        };
+        let before = under_pointer(&memory);
+
+        let mut zooms = Vec::new();
+        for n in 0..60 {
+            let mut events = vec![Event::PointerMoved(pointer)];
+            if n == 1 {
+                events.push(Event::MouseWheel {
+                    unit: egui::MouseWheelUnit::Point,
+                    delta: Vec2::new(0., 10.),
+                    phase: egui::TouchPhase::Move,
+                    modifiers: Modifiers::NONE,
+                });
+            }
+
+            run_frame(
+                &ctx,
+                n as f64 / 60.,
+                events,
+                &mut memory,
+                my_position,
+                |map| map.zoom_with_ctrl(false).zoom_snap(true),
+            );
+            zooms.push(memory.zoom());
+        }
+
+        // Zoom changed continuously during the gesture, but settled at an integer level.
+        assert!(zooms.iter().any(|zoom| zoom.fract() != 0.));
+        assert_eq!(memory.zoom().fract(), 0.);
+        assert!(!memory.animating());
+
+        let after = under_pointer(&memory);
+        assert!(
+            (after.x() - before.x()).abs() < 1e-4,
+            "{before:?} {after:?}"
+        );
+        assert!(
+            (after.y() - before.y()).abs() < 1e-4,
+            "{before:?} {after:?}"
+        );
+    }
+
+    #[test]
+    fn scroll_zoom_sensitivity() {
+        let default = scroll_zoom(None);
+        assert!(default > 0.);
+        let slower = scroll_zoom(Some(0.125));
+        assert!(0. < slower && slower < default, "{slower} {default}");
+    }
+
+    #[test]
+    fn pinch_zoom_keeps_the_centroid_in_place() {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::new(my_position, 12.).expect("valid zoom");
+        let centroid = pos2(300., 100.);
+        let touch = |id, phase, offset: f32| Event::Touch {
+            device_id: egui::TouchDeviceId(0),
+            id: egui::TouchId(id),
+            phase,
+            pos: centroid + Vec2::new(offset * if id == 0 { -1. } else { 1. }, 0.),
+            force: None,
+        };
+        let both = |phase, offset| vec![touch(0, phase, offset), touch(1, phase, offset)];
+
+        let mut rect = Rect::NOTHING;
+        let mut under_centroid = None;
+        for n in 0..10 {
+            let events = match n {
+                // Mouse pointer somewhere else, which must not be used as the anchor.
+                0 => vec![Event::PointerMoved(pos2(50., 250.))],
+                1 => both(egui::TouchPhase::Start, 20.),
+                9 => both(egui::TouchPhase::End, 20. + 10. * 8.),
+                _ => both(egui::TouchPhase::Move, 20. + 10. * (n - 1) as f32),
+            };
+            rect = run_frame(
+                &ctx,
+                n as f64 / 60.,
+                events,
+                &mut memory,
+                my_position,
+                |map| map,
+            );
+
+            under_centroid.get_or_insert_with(|| {
+                ScreenProjector::new(&MercatorProjection, rect, &memory, my_position)
+                    .unproject(centroid)
+            });
+        }
+
+        assert!(memory.zoom() > 13., "{}", memory.zoom());
+        let projector = ScreenProjector::new(&MercatorProjection, rect, &memory, my_position);
+        let moved = projector.project(under_centroid.expect("first frame ran")) - centroid;
+        assert!(moved.length() < 0.5, "{moved:?}");
+    }
+
+    #[test]
+    fn interactions_are_reported() {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::new(my_position, 12.).expect("valid zoom");
+        let middle = pos2(200., 150.);
+        let scroll = Event::MouseWheel {
+            unit: egui::MouseWheelUnit::Point,
+            delta: Vec2::new(0., 10.),
+            phase: egui::TouchPhase::Move,
+            modifiers: Modifiers::NONE,
+        };
+
+        let mut interactions = Vec::new();
+        for n in 0..120 {
+            let events = match n {
+                0 => vec![Event::PointerMoved(middle)],
+                1 => vec![click(middle, true)],
+                2 => vec![Event::PointerMoved(middle + Vec2::new(50., 0.))],
+                3 => vec![click(middle + Vec2::new(50., 0.), false)],
+                100 => vec![scroll.clone()],
+                _ => vec![],
+            };
+            run_frame(
+                &ctx,
+                n as f64 / 60.,
+                events,
+                &mut memory,
+                my_position,
+                |map| map.zoom_with_ctrl(false),
+            );
+            interactions.extend(memory.take_last_interaction());
+        }
+
+        // Zooming by scrolling is smoothed over a few frames.
+        interactions.dedup();
+        assert_eq!(
+            interactions,
+            [
+                Interaction::Panned,
+                Interaction::Settled,
+                Interaction::Zoomed
+            ]
+        );
+    }
+
+    #[test]
+    fn long_press_is_reported_once() {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::new(my_position, 12.).expect("valid zoom");
+        let finger = pos2(300., 100.);
+        let touch = |phase| Event::Touch {
+            device_id: egui::TouchDeviceId(0),
+            id: egui::TouchId(0),
+            phase,
+            pos: finger,
+            force: None,
+        };
+
+        let mut long_pressed_at = Vec::new();
+        for n in 0..=60 {
+            let events = match n {
+                // Let the map register itself for hit testing first.
+                0 => vec![Event::PointerMoved(finger)],
+                1 => vec![touch(egui::TouchPhase::Start), click(finger, true)],
+                60 => vec![touch(egui::TouchPhase::End), click(finger, false)],
+                _ => vec![touch(egui::TouchPhase::Move)],
+            };
+            let _ = run_ui_frame(&ctx, n as f64 / 30., events, |ui| {
+                let map = Map::new(MercatorProjection, &mut memory, my_position);
+                map.show(ui, |_, response, projector, _| {
+                    if response.long_touched() {
+                        long_pressed_at.extend(
+                            response
+                                .interact_pointer_pos()
+                                .map(|pos| projector.unproject(pos)),
+                        );
+                    }
+                });
+            });
+        }
+
+        assert_eq!(long_pressed_at.len(), 1);
+        let projector = ScreenProjector::new(&MercatorProjection, screen(), &memory, my_position);
+        let moved = projector.project(long_pressed_at[0]) - finger;
+        assert!(moved.length() < 0.5, "{moved:?}");
+    }
+
+    #[test]
+    fn background_is_painted_first() {
+        let ctx = egui::Context::default();
+        let mut memory = MapMemory::default();
+        let background = Color32::from_rgb(170, 211, 223);
+
+        let output = run_ui_frame(&ctx, 0., Vec::new(), |ui| {
+            ui.add(
+                Map::new(MercatorProjection, &mut memory, lon_lat(17., 51.))
+                    .with_background(background),
+            );
+        });
+
+        let fills: Vec<_> = output
+            .shapes
+            .iter()
+            .filter_map(|clipped| match &clipped.shape {
+                egui::Shape::Rect(rect) => Some(rect.fill),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fills.first(), Some(&background));
+    }
+}
@@ -1,38 +1,213 @@
+use std::time::Duration;
+
+use egui::{Pos2, Rect, Vec2};
+
 use crate::{
-    InvalidZoom, Position, center::Center, position::AdjustedPosition, projector::Projection,
+    InvalidZoom, Position,
+    center::{Center, ease_out},
+    lon_lat,
+    position::{AdjustedPosition, Pixels, PixelsExt as _},
+    projector::Projection,
     zoom::Zoom,
 };
 
 /// State of the map widget which must persist between frames.
+///
+/// With the `serde` feature, only the center and zoom are serialized. Pending requests,
+/// animations and gestures are transient, so they are not restored.
 #[derive(Debug, Default, Clone)]
 #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct MapMemory {
     pub(crate) center_mode: Center,
     pub(crate) zoom: Zoom,
+    /// Panning requested while following `my_position`. It is applied by the next frame of the
+    /// map widget, which knows where `my_position` is.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) pending_pan: Option<PendingPan>,
+    /// Duration of the animation back to `my_position` requested by
+    /// [`MapMemory::recenter_animated`], in seconds. Like panning, it is started by the next
+    /// frame.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) pending_recenter: Option<f32>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) zoom_animation: Option<ZoomAnimation>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) last_interaction: Option<Interaction>,
+    /// `my_position` with its jitter smoothed out, see [`crate::Map::smooth_my_position`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) smoothed_my_position: Option<Position>,
+    /// Screen position the ongoing zoom gesture is anchored at, if there is one.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) zoom_gesture_focus: Option<Pos2>,
+}
+
+/// How the user changed the map, see [`MapMemory::take_last_interaction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum Interaction {
+    /// Zoom level was changed by a gesture, e.g. scrolling, pinching or double clicking.
+    Zoomed,
+    /// Map was moved by dragging, scrolling or keyboard.
+    Panned,
+    /// Map stopped moving after being panned, i.e. it was released without inertia, or it
+    /// finished gliding or being pulled back to `my_position`.
+    Settled,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PendingPan {
+    delta: Vec2,
+    smooth: bool,
+}
+
+/// Zoom changing gradually over time, started by [`MapMemory::set_zoom_animated`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ZoomAnimation {
+    from: f64,
+    to: f64,
+    /// Duration of the animation, in seconds.
+    duration: f32,
+    elapsed: f32,
+    /// Screen position which stays fixed while zooming. Map's center if `None`.
+    focus: Option<Pos2>,
+}
+
+impl ZoomAnimation {
+    /// Zoom level at the current point of the animation, eased out so that it slows down
+    /// towards the target.
+    fn zoom(&self) -> f64 {
+        let t = if self.duration > 0. {
+            (self.elapsed / self.duration).clamp(0., 1.) as f64
+        } else {
+            1.
+        };
+        self.from + (self.to - self.from) * ease_out(t)
+    }
+
+    fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
 }
 
 impl MapMemory {
+    /// Memory of a map pointed exactly at the given geographical position and zoom level, so that
+    /// the very first frame shows that view. Same as [`MapMemory::default`] followed by
+    /// [`MapMemory::with_zoom`] and [`MapMemory::with_center`].
+    pub fn new(center: Position, zoom: f64) -> Result<Self, InvalidZoom> {
+        Ok(Self::default().with_zoom(zoom)?.with_center(center))
+    }
+
+    /// Start at given zoom level instead of the default one.
+    ///
+    /// ```
+    /// # use walkers::{MapMemory, lon_lat};
+    /// let memory = MapMemory::default()
+    ///     .with_zoom(12.)?
+    ///     .with_center(lon_lat(17.03664, 51.09916));
+    /// # Ok::<(), walkers::InvalidZoom>(())
+    /// ```
+    pub fn with_zoom(mut self, zoom: f64) -> Result<Self, InvalidZoom> {
+        self.set_zoom(zoom)?;
+        Ok(self)
+    }
+
+    /// Start pointed exactly at the given geographical position, instead of following
+    /// `my_position`.
+    pub fn with_center(mut self, position: Position) -> Self {
+        self.center_at(position);
+        self
+    }
+
     /// Try to zoom in, returning `Err(InvalidZoom)` if already at maximum.
     pub fn zoom_in(&mut self) -> Result<(), InvalidZoom> {
+        self.zoom_animation = None;
         self.zoom.zoom_in()
     }
 
     /// Try to zoom out, returning `Err(InvalidZoom)` if already at minimum.
     pub fn zoom_out(&mut self) -> Result<(), InvalidZoom> {
+        self.zoom_animation = None;
         self.zoom.zoom_out()
     }
 
-    /// Set exact zoom level
+    /// Set exact zoom level. Position at the map's center stays exactly the same, see
+    /// [`MapMemory::set_zoom_at`] for zooming around a different point.
     pub fn set_zoom(&mut self, zoom: f64) -> Result<(), InvalidZoom> {
         self.zoom = Zoom::try_from(zoom)?;
+        self.zoom_animation = None;
+        Ok(())
+    }
+
+    /// Set exact zoom level, keeping the `focus` screen position fixed instead of the map's
+    /// center, the same way zooming with the mouse wheel does. The `rect` is where the map is
+    /// shown, e.g. [`egui::Response::rect`]. Unless the `focus` is the center, the map gets
+    /// detached from `my_position`.
+    pub fn set_zoom_at(&mut self, zoom: f64, focus: Pos2, rect: Rect) -> Result<(), InvalidZoom> {
+        let factor = 2f64.powf(zoom - self.zoom());
+        self.set_zoom(zoom)?;
+
+        // Position under the focus moved away from the center by the zoom factor, so bring it
+        // back.
+        let offset = focus - rect.center();
+        if offset != Vec2::ZERO {
+            self.pan(offset * (1. - factor as f32), false);
+        }
         Ok(())
     }
 
+    /// Gradually change the zoom level to `target` over `duration`, e.g. 200 ms. The `focus` is
+    /// a screen position which stays in place while zooming, typically the mouse pointer. If
+    /// `None`, zoom is anchored at the map's center.
+    ///
+    /// The animation is interrupted by zoom gestures and by other methods setting the zoom.
+    pub fn set_zoom_animated(
+        &mut self,
+        target: f64,
+        duration: Duration,
+        focus: Option<Pos2>,
+    ) -> Result<(), InvalidZoom> {
+        Zoom::try_from(target)?;
+        self.zoom_animation = Some(ZoomAnimation {
+            from: self.zoom(),
+            to: target,
+            duration: duration.as_secs_f32(),
+            elapsed: 0.,
+            focus,
+        });
+        Ok(())
+    }
+
+    /// Advance the zoom animation by `delta_time` seconds. Returns by how much the zoom should
+    /// change and the screen position to anchor it at.
+    pub(crate) fn advance_zoom_animation(
+        &mut self,
+        delta_time: f32,
+    ) -> Option<(f64, Option<Pos2>)> {
+        let current: f64 = self.zoom.into();
+        let animation = self.zoom_animation.as_mut()?;
+        animation.elapsed += delta_time;
+
+        let delta = animation.zoom() - current;
+        let focus = animation.focus;
+
+        if animation.finished() {
+            self.zoom_animation = None;
+        }
+
+        Some((delta, focus))
+    }
+
     /// Returns the current zoom level
     pub fn zoom(&self) -> f64 {
         self.zoom.into()
     }
 
+    /// Returns the current zoom level rounded to the one tiles are fetched for. Note that
+    /// sources with 512 px tiles use one level less, as their tiles cover four 256 px ones.
+    pub fn zoom_level(&self) -> u8 {
+        self.zoom.round()
+    }
+
     /// If the map is in detached state, returns the geographical position
     /// of the center. `None` if the map is not detached, i.e. following
     /// `my_position`.
@@ -40,19 +215,628 @@ impl MapMemory {
         self.center_mode.detached(projection)
     }
 
-    /// Whether the map is currently animating. Dragging, zooming and `my_position` changes are not
+    /// Whether `position` is visible on the map shown in `rect`. Positions closer than `margin`
+    /// points to the edge are considered not visible, which is handy for recentering before a
+    /// tracked target leaves the view.
+    pub fn is_visible<P: Projection + ?Sized>(
+        &self,
+        projection: &P,
+        my_position: Position,
+        position: Position,
+        rect: Rect,
+        margin: f32,
+    ) -> bool {
+        let center = self.center_mode.position(my_position, projection);
+        let offset = short_way_offset(projection, center, position, self.zoom());
+
+        rect.shrink(margin)
+            .contains(rect.center() + offset.to_vec2())
+    }
+
+    /// Geographical position shown at `screen_position` of the map shown in `rect`. Same as
+    /// [`crate::ScreenProjector::unproject`], but does not need a projector, e.g. for showing
+    /// the position under the cursor outside of the map widget.
+    pub fn screen_to_position<P: Projection + ?Sized>(
+        &self,
+        projection: &P,
+        my_position: Position,
+        screen_position: Pos2,
+        rect: Rect,
+    ) -> Position {
+        let zoom = self.zoom();
+        let center = self.center_mode.position(my_position, projection);
+        let offset = screen_position - rect.center();
+        let pixels = projection.position_to_pixels(center, zoom)
+            + Pixels::new(offset.x as f64, offset.y as f64);
+
+        projection.pixels_to_position(pixels, zoom)
+    }
+
+    /// Whether the map is currently animating, e.g. gliding after being released, or zooming
+    /// started by [`MapMemory::set_zoom_animated`] or by snapping to an integer zoom level.
+    /// Dragging, zoom gestures like scrolling or pinching, and `my_position` changes are not
     /// considered animation.
     pub fn animating(&self) -> bool {
-        self.center_mode.animating()
+        self.center_mode.animating() || self.zoom_animation.is_some()
+    }
+
+    /// The most recent way the user changed the map since the last call, if any. Unlike
+    /// [`egui::Response::changed`], it tells zooming and panning apart, and when the movement is
+    /// over, which is handy e.g. for fetching data for the visible area once the map settles.
+    pub fn take_last_interaction(&mut self) -> Option<Interaction> {
+        self.last_interaction.take()
     }
 
     /// Point the map exactly at the given geographical position.
     pub fn center_at(&mut self, position: Position) {
         self.center_mode = Center::Exact(AdjustedPosition::new(position));
+        self.pending_recenter = None;
     }
 
     /// Start following `my_position` given in [`crate::Map::new`].
     pub fn follow_my_position(&mut self) {
         self.center_mode = Center::MyPosition;
+        self.pending_pan = None;
+        self.pending_recenter = None;
+    }
+
+    /// Like [`MapMemory::follow_my_position`], but the map glides back to `my_position` over
+    /// `duration`, e.g. 500 ms, instead of jumping there. Dragging the map interrupts it.
+    ///
+    /// How far off-center the map is can be told by [`MapMemory::detached`], e.g. passed to
+    /// [`crate::distance_meters`] along with `my_position`.
+    pub fn recenter_animated(&mut self, duration: Duration) {
+        if !matches!(self.center_mode, Center::MyPosition) {
+            self.pending_recenter = Some(duration.as_secs_f32());
+        }
+    }
+
+    /// Move the map by given number of pixels at the current zoom level, the same way dragging
+    /// does, i.e. positive `x` reveals what is to the west. The map becomes detached.
+    pub fn pan_pixels(&mut self, delta: Vec2) {
+        self.pan(delta, false);
+    }
+
+    /// Like [`MapMemory::pan_pixels`], but the map glides smoothly to the target, slowing down
+    /// like after releasing a drag.
+    pub fn pan_to_offset(&mut self, delta: Vec2) {
+        self.pan(delta, true);
+    }
+
+    fn pan(&mut self, delta: Vec2, smooth: bool) {
+        if let Some(center_mode) = self.center_mode.panned(delta, self.zoom.into(), smooth) {
+            self.center_mode = center_mode;
+        } else {
+            let pending = self.pending_pan.get_or_insert(PendingPan {
+                delta: Vec2::ZERO,
+                smooth,
+            });
+            pending.delta += delta;
+            pending.smooth = smooth;
+        }
+    }
+
+    /// Keep the map from being panned past the poles, so that no blank space shows up above or
+    /// below the world shown in a viewport of given `height`. When the whole world is shorter
+    /// than the viewport, it is centered vertically.
+    pub(crate) fn clamp_to_poles<P: Projection + ?Sized>(&mut self, projection: &P, height: f32) {
+        if !projection.is_geographic() {
+            return;
+        }
+
+        let Some(center) = self.center_mode.detached(projection) else {
+            return;
+        };
+
+        let zoom = self.zoom.into();
+        let y = projection.position_to_pixels(center, zoom).y();
+        let (north, south) = if projection.is_mercator() {
+            // Mercator stretches the poles to infinity, so the world ends where the tiles do.
+            (0., crate::mercator::total_pixels(zoom))
+        } else {
+            (
+                projection.position_to_pixels(lon_lat(0., 90.), zoom).y(),
+                projection.position_to_pixels(lon_lat(0., -90.), zoom).y(),
+            )
+        };
+        let half_height = height as f64 / 2.;
+
+        let clamped = if south - north <= height as f64 {
+            (north + south) / 2.
+        } else {
+            y.clamp(north + half_height, south - half_height)
+        };
+
+        if clamped != y {
+            self.center_mode = self
+                .center_mode
+                .clone()
+                .shift(Vec2::new(0., (y - clamped) as f32), zoom);
+        }
+    }
+
+    /// Start the animation requested by [`MapMemory::recenter_animated`], now that
+    /// `my_position` is known.
+    pub(crate) fn apply_pending_recenter<P: Projection + ?Sized>(
+        &mut self,
+        projection: &P,
+        my_position: Position,
+    ) {
+        let Some(duration) = self.pending_recenter.take() else {
+            return;
+        };
+        let Some(center) = self.center_mode.detached(projection) else {
+            return;
+        };
+
+        let zoom = self.zoom.into();
+        let offset = short_way_offset(projection, center, my_position, zoom);
+
+        self.center_mode = Center::Recentering {
+            from: AdjustedPosition::new(my_position).shift(offset.to_vec2(), zoom),
+            duration,
+            elapsed: 0.,
+        };
+    }
+
+    /// Move the smoothed `my_position` towards the raw one, with the exponential moving average
+    /// of given `time_constant`, in seconds. Returns the smoothed position and whether it caught
+    /// up with the raw one.
+    pub(crate) fn smooth_my_position<P: Projection + ?Sized>(
+        &mut self,
+        projection: &P,
+        my_position: Position,
+        time_constant: f32,
+        delta_time: f32,
+    ) -> (Position, bool) {
+        let Some(smoothed) = self.smoothed_my_position else {
+            self.smoothed_my_position = Some(my_position);
+            return (my_position, true);
+        };
+
+        let zoom = self.zoom.into();
+        let from = projection.position_to_pixels(smoothed, zoom);
+        let offset = short_way_offset(projection, smoothed, my_position, zoom);
+
+        let factor = if time_constant > 0. {
+            1. - (-delta_time / time_constant).exp()
+        } else {
+            1.
+        };
+        let remaining = offset * (1. - factor as f64);
+
+        // Sub-pixel differences are not visible, so let the map go idle.
+        let (smoothed, settled) = if remaining.to_vec2().length() < 0.1 {
+            (my_position, true)
+        } else {
+            let smoothed = projection.pixels_to_position(from + offset * factor as f64, zoom);
+            if projection.is_geographic() {
+                (crate::mercator::wrap_longitude(smoothed), false)
+            } else {
+                (smoothed, false)
+            }
+        };
+
+        self.smoothed_my_position = Some(smoothed);
+        (smoothed, settled)
+    }
+
+    /// Apply panning requested while the map was following `my_position`.
+    pub(crate) fn apply_pending_pan(&mut self, my_position: Position) {
+        if let Some(PendingPan { delta, smooth }) = self.pending_pan.take() {
+            self.center_mode = Center::Exact(AdjustedPosition::new(my_position));
+            self.pan(delta, smooth);
+        }
+    }
+}
+
+/// Offset in pixels from `from` to `to`, going the short way around, across the antimeridian if
+/// needed.
+fn short_way_offset<P: Projection + ?Sized>(
+    projection: &P,
+    from: Position,
+    to: Position,
+    zoom: f64,
+) -> Pixels {
+    let mut offset =
+        projection.position_to_pixels(to, zoom) - projection.position_to_pixels(from, zoom);
+    if projection.is_geographic() {
+        let world_width = crate::mercator::total_pixels(zoom);
+        offset.set_x(offset.x() - (offset.x() / world_width).round() * world_width);
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MercatorProjection, lon_lat};
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn memory_persisted_by_earlier_versions_can_be_restored() {
+        let memory: MapMemory = serde_json::from_str(r#"{"center_mode":"MyPosition","zoom":12.5}"#)
+            .expect("deserializable memory");
+        assert_eq!(memory.zoom(), 12.5);
+        assert!(memory.detached(&MercatorProjection).is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn transient_state_is_not_persisted() {
+        let mut memory = MapMemory::default();
+        memory.center_at(lon_lat(17., 51.));
+        memory
+            .set_zoom_animated(10., Duration::from_millis(200), None)
+            .expect("valid zoom");
+        memory.recenter_animated(Duration::from_millis(200));
+
+        let json = serde_json::to_string(&memory).expect("serializable memory");
+        assert!(!json.contains("animation"), "{json}");
+        assert!(!json.contains("recenter"), "{json}");
+
+        let restored: MapMemory = serde_json::from_str(&json).expect("deserializable memory");
+        assert_eq!(restored.zoom(), memory.zoom());
+        assert!(restored.zoom_animation.is_none());
+        assert!(restored.pending_recenter.is_none());
+    }
+
+    fn pixels_between(a: Position, b: Position, zoom: f64) -> Vec2 {
+        let projection = MercatorProjection;
+        let a = projection.position_to_pixels(a, zoom);
+        let b = projection.position_to_pixels(b, zoom);
+        Vec2::new((b.x() - a.x()) as f32, (b.y() - a.y()) as f32)
+    }
+
+    #[test]
+    fn visibility_of_positions() {
+        let my_position = lon_lat(17., 51.);
+        let memory = MapMemory::default();
+        let rect = Rect::from_min_size(egui::Pos2::ZERO, Vec2::new(200., 100.));
+
+        // 40 pixels to the west of the center.
+        let mut west = memory.clone();
+        west.center_at(my_position);
+        west.pan_pixels(Vec2::new(40., 0.));
+        let position = west
+            .detached(&MercatorProjection)
+            .expect("map should be detached");
+
+        assert!(memory.is_visible(&MercatorProjection, my_position, my_position, rect, 0.));
+        assert!(memory.is_visible(&MercatorProjection, my_position, position, rect, 0.));
+        assert!(memory.is_visible(&MercatorProjection, my_position, position, rect, 50.));
+        assert!(!memory.is_visible(&MercatorProjection, my_position, position, rect, 70.));
+        assert!(!memory.is_visible(
+            &MercatorProjection,
+            my_position,
+            lon_lat(18., 51.),
+            rect,
+            0.
+        ));
+    }
+
+    #[test]
+    fn visibility_across_the_antimeridian() {
+        let center = lon_lat(179.9, 0.);
+        let memory = MapMemory::new(center, 10.).expect("valid zoom");
+        let rect = Rect::from_min_size(egui::Pos2::ZERO, Vec2::new(400., 300.));
+        let position = lon_lat(-179.9, 0.);
+
+        assert!(memory.is_visible(&MercatorProjection, center, position, rect, 0.));
+        let projector = crate::ScreenProjector::new(&MercatorProjection, rect, &memory, center);
+        assert!(rect.contains(projector.project(position)));
+
+        assert!(!memory.is_visible(&MercatorProjection, center, lon_lat(-179.7, 0.), rect, 0.));
+    }
+
+    #[test]
+    fn zoom_level_is_rounded() {
+        let mut memory = MapMemory::default();
+
+        memory.set_zoom(12.4).expect("valid zoom");
+        assert_eq!(memory.zoom_level(), 12);
+
+        memory.set_zoom(12.6).expect("valid zoom");
+        assert_eq!(memory.zoom_level(), 13);
+        assert_eq!(memory.zoom(), 12.6);
+    }
+
+    #[test]
+    fn setting_zoom_keeps_the_center() {
+        let mut memory = MapMemory::new(lon_lat(17., 51.), 5.).expect("valid zoom");
+        // Center which carries a pixel offset, like after dragging.
+        memory.pan_pixels(Vec2::new(123.4, -56.7));
+        let center = memory.detached(&MercatorProjection);
+
+        memory.set_zoom(12.).expect("valid zoom");
+        assert_eq!(memory.detached(&MercatorProjection), center);
+
+        memory.zoom_out().expect("valid zoom");
+        assert_eq!(memory.detached(&MercatorProjection), center);
+    }
+
+    #[test]
+    fn recentering_glides_back_to_my_position() {
+        let my_position = lon_lat(-179.9, 0.);
+        let mut memory = MapMemory::new(lon_lat(179.9, 0.), 10.).expect("valid zoom");
+
+        memory.recenter_animated(Duration::from_millis(500));
+        memory.apply_pending_recenter(&MercatorProjection, my_position);
+        let start = memory
+            .detached(&MercatorProjection)
+            .expect("still detached");
+        assert!((start.x() - 179.9).abs() < 1e-6, "{start:?}");
+
+        for _ in 0..15 {
+            memory.center_mode.update_movement(1. / 60., memory.zoom());
+        }
+
+        // Halfway in time, but more than halfway in distance, across the antimeridian.
+        let halfway = memory
+            .detached(&MercatorProjection)
+            .expect("still detached");
+        let distance = |position| crate::distance_meters(position, my_position);
+        assert!(distance(halfway) < 0.5 * distance(start), "{halfway:?}");
+
+        for _ in 0..15 {
+            memory.center_mode.update_movement(1. / 60., memory.zoom());
+        }
+        assert!(memory.detached(&MercatorProjection).is_none());
+        assert!(!memory.animating());
+    }
+
+    #[test]
+    fn my_position_is_smoothed() {
+        let mut memory = MapMemory::default();
+        let mut smooth =
+            |position| memory.smooth_my_position(&MercatorProjection, position, 0.5, 1. / 60.);
+
+        // First position is taken as it is.
+        assert_eq!(smooth(lon_lat(179.99, 0.)), (lon_lat(179.99, 0.), true));
+
+        // Jump across the antimeridian is followed gradually, the short way around.
+        let raw = lon_lat(-179.99, 0.);
+        let (smoothed, settled) = smooth(raw);
+        assert!(!settled);
+        assert!(smoothed.x() > 179.99, "{smoothed:?}");
+
+        let mut frames = 1;
+        while !smooth(raw).1 {
+            frames += 1;
+        }
+        assert!((30..300).contains(&frames), "{frames}");
+        assert_eq!(smooth(raw), (raw, true));
+    }
+
+    #[test]
+    fn zooming_at_focus_keeps_it_in_place() {
+        let my_position = lon_lat(17., 51.);
+        let rect = Rect::from_min_size(egui::Pos2::ZERO, Vec2::new(400., 300.));
+        let focus = egui::pos2(300., 50.);
+
+        for detached in [true, false] {
+            let mut memory = MapMemory::default();
+            if detached {
+                memory.center_at(lon_lat(16., 50.));
+            }
+
+            let projector =
+                crate::ScreenProjector::new(&MercatorProjection, rect, &memory, my_position);
+            let under_focus = projector.unproject(focus);
+
+            memory.set_zoom_at(13.5, focus, rect).expect("valid zoom");
+            memory.apply_pending_pan(my_position);
+
+            assert_eq!(memory.zoom(), 13.5);
+            let projector =
+                crate::ScreenProjector::new(&MercatorProjection, rect, &memory, my_position);
+            let moved = projector.project(under_focus) - focus;
+            assert!(moved.length() < 0.5, "{moved:?}");
+        }
+    }
+
+    #[test]
+    fn animated_zoom_reaches_the_target() {
+        let mut memory = MapMemory::default();
+        memory.set_zoom(10.).expect("valid zoom");
+        memory
+            .set_zoom_animated(12., Duration::from_millis(200), None)
+            .expect("valid zoom");
+        assert!(memory.animating());
+
+        let mut previous = memory.zoom();
+        for _ in 0..12 {
+            let (delta, focus) = memory
+                .advance_zoom_animation(1. / 60.)
+                .expect("animation should be running");
+            assert_eq!(focus, None);
+            assert!(delta > 0.);
+
+            memory.zoom.zoom_by(delta);
+            assert!(memory.zoom() > previous);
+            previous = memory.zoom();
+        }
+
+        assert!(!memory.animating());
+        assert!((memory.zoom() - 12.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn animated_zoom_is_validated_and_interrupted() {
+        let mut memory = MapMemory::default();
+        assert_eq!(
+            memory
+                .set_zoom_animated(30., Duration::from_millis(200), None)
+                .map_err(|err| err.requested),
+            Err(30.)
+        );
+
+        memory
+            .set_zoom_animated(12., Duration::from_millis(200), None)
+            .expect("valid zoom");
+        memory.set_zoom(5.).expect("valid zoom");
+        assert!(!memory.animating());
+        assert_eq!(memory.advance_zoom_animation(1. / 60.), None);
+    }
+
+    #[test]
+    fn panning_detached_map() {
+        let start = lon_lat(17., 51.);
+        let mut memory = MapMemory::default();
+        memory.center_at(start);
+
+        memory.pan_pixels(Vec2::new(100., -50.));
+
+        let end = memory
+            .detached(&MercatorProjection)
+            .expect("map should be detached");
+        let moved = pixels_between(start, end, memory.zoom());
+        assert!((moved - Vec2::new(-100., 50.)).length() < 0.01, "{moved:?}");
+    }
+
+    #[test]
+    fn panning_across_the_antimeridian_wraps_around() {
+        let mut memory = MapMemory::default();
+        memory.set_zoom(5.).expect("valid zoom");
+        memory.center_at(lon_lat(179.9, 0.));
+
+        // Whole world is 8192 pixels wide at this zoom.
+        memory.pan_pixels(Vec2::new(-8192. / 360. * 2., 0.));
+
+        let end = memory
+            .detached(&MercatorProjection)
+            .expect("map should be detached");
+        assert!((end.x() - -178.1).abs() < 1e-6, "{end:?}");
+    }
+
+    #[test]
+    fn panning_stops_at_the_poles() {
+        let rect = Rect::from_min_size(egui::Pos2::ZERO, Vec2::new(400., 300.));
+        let mut memory = MapMemory::default();
+        memory.set_zoom(2.).expect("valid zoom");
+        memory.center_at(lon_lat(0., 80.));
+
+        // Way past the north pole.
+        memory.pan_pixels(Vec2::new(0., 2000.));
+        memory.clamp_to_poles(&MercatorProjection, rect.height());
+
+        let center = memory.detached(&MercatorProjection).expect("detached");
+        assert!(center.y() < 85.0511, "{center:?}");
+        let projector =
+            crate::ScreenProjector::new(&MercatorProjection, rect, &memory, lon_lat(0., 0.));
+        assert!((projector.unproject(rect.center_top()).y() - 85.0511).abs() < 1e-3);
+
+        // Whole world is shorter than the viewport at zoom 0, so it stays in the middle.
+        memory.set_zoom(0.).expect("valid zoom");
+        memory.clamp_to_poles(&MercatorProjection, rect.height());
+        let center = memory.detached(&MercatorProjection).expect("detached");
+        assert!(center.y().abs() < 1e-4, "{center:?}");
+    }
+
+    #[test]
+    fn panning_stops_at_the_poles_of_plate_carree() {
+        let projection = crate::PlateCarreeProjection;
+        let rect = Rect::from_min_size(egui::Pos2::ZERO, Vec2::new(400., 300.));
+        let mut memory = MapMemory::default();
+        memory.set_zoom(3.).expect("valid zoom");
+        memory.center_at(lon_lat(170., -80.));
+
+        // Way past the south pole, into the empty lower half of the tile grid, and across the
+        // antimeridian.
+        memory.pan_pixels(Vec2::new(-100., -2000.));
+        memory.clamp_to_poles(&projection, rect.height());
+
+        let center = memory.detached(&projection).expect("detached");
+        assert!((-180. ..=180.).contains(&center.x()), "{center:?}");
+        let projector = crate::ScreenProjector::new(&projection, rect, &memory, lon_lat(0., 0.));
+        assert!((projector.unproject(rect.center_bottom()).y() + 90.).abs() < 1e-3);
+
+        // Way past the north pole.
+        memory.pan_pixels(Vec2::new(0., 4000.));
+        memory.clamp_to_poles(&projection, rect.height());
+        let projector = crate::ScreenProjector::new(&projection, rect, &memory, lon_lat(0., 0.));
+        assert!((projector.unproject(rect.center_top()).y() - 90.).abs() < 1e-3);
+
+        // Whole world is shorter than the viewport at zoom 0, so it stays in the middle.
+        memory.set_zoom(0.).expect("valid zoom");
+        memory.clamp_to_poles(&projection, rect.height());
+        let center = memory.detached(&projection).expect("detached");
+        assert!(center.y().abs() < 1e-4, "{center:?}");
+    }
+
+    #[test]
+    fn panning_while_following_my_position() {
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::default();
+
+        memory.pan_pixels(Vec2::new(30., 0.));
+        memory.pan_pixels(Vec2::new(30., 0.));
+        assert_eq!(memory.detached(&MercatorProjection), None);
+
+        memory.apply_pending_pan(my_position);
+
+        let end = memory
+            .detached(&MercatorProjection)
+            .expect("map should be detached");
+        let moved = pixels_between(my_position, end, memory.zoom());
+        assert!((moved - Vec2::new(-60., 0.)).length() < 0.01, "{moved:?}");
+    }
+
+    #[test]
+    fn smooth_panning_ends_at_the_same_place() {
+        let start = lon_lat(17., 51.);
+        let mut memory = MapMemory::default();
+        memory.center_at(start);
+
+        memory.pan_to_offset(Vec2::new(0., 200.));
+        assert!(memory.animating());
+
+        while memory.animating() {
+            memory.center_mode.update_movement(1. / 60., memory.zoom());
+        }
+
+        let end = memory
+            .detached(&MercatorProjection)
+            .expect("map should be detached");
+        let moved = pixels_between(start, end, memory.zoom());
+        assert!((moved - Vec2::new(0., -200.)).length() < 0.01, "{moved:?}");
+    }
+
+    #[test]
+    fn builder_style_initialization() {
+        let position = lon_lat(17., 51.);
+        let memories = [
+            MapMemory::default()
+                .with_zoom(12.)
+                .expect("valid zoom")
+                .with_center(position),
+            MapMemory::new(position, 12.).expect("valid zoom"),
+        ];
+
+        for memory in memories {
+            assert_eq!(memory.zoom(), 12.);
+            let center = memory.detached(&MercatorProjection).expect("detached");
+            assert!((center.x() - position.x()).abs() < 1e-9);
+            assert!((center.y() - position.y()).abs() < 1e-9);
+        }
+
+        assert!(MapMemory::default().with_zoom(-1.).is_err());
+        assert!(MapMemory::new(position, 27.).is_err());
+    }
+
+    #[test]
+    fn screen_to_position_matches_projector() {
+        let my_position = lon_lat(17., 51.);
+        let memory = MapMemory::new(lon_lat(16., 50.), 10.).expect("valid zoom");
+        let rect = Rect::from_min_size(egui::pos2(10., 20.), Vec2::new(400., 300.));
+        let screen_position = egui::pos2(50., 250.);
+
+        let expected = crate::ScreenProjector::new(&MercatorProjection, rect, &memory, my_position)
+            .unproject(screen_position);
+        let actual =
+            memory.screen_to_position(&MercatorProjection, my_position, screen_position, rect);
+
+        assert!((expected.x() - actual.x()).abs() < 1e-9);
+        assert!((expected.y() - actual.y()).abs() < 1e-9);
     }
 }
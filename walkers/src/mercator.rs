@@ -41,13 +41,19 @@ fn mercator_normalized(position: Position) -> (f64, f64) {
     (x, y)
 }
 
+/// Bring the longitude back to the `-180..180` range, as if going around the globe.
+pub(crate) fn wrap_longitude(position: Position) -> Position {
+    if (-180. ..=180.).contains(&position.x()) {
+        position
+    } else {
+        lon_lat((position.x() + 180.).rem_euclid(360.) - 180., position.y())
+    }
+}
+
 /// Calculate the tile coordinated for the given position.
-pub(crate) fn tile_id(position: Position, mut zoom: u8, source_tile_size: u32) -> TileId {
+pub(crate) fn tile_id(position: Position, zoom: u8, source_tile_size: u32) -> TileId {
     let (x, y) = mercator_normalized(position);
-
-    // Some sources provide larger tiles, effectively bundling e.g. 4 256px tiles in one
-    // 512px one. Walkers uses 256px internally, so we need to adjust the zoom level.
-    zoom -= (source_tile_size as f64 / TILE_SIZE as f64).log2() as u8;
+    let zoom = tile_zoom(zoom, source_tile_size);
 
     // Map that into a big bitmap made out of web tiles.
     let number_of_tiles = 2u32.pow(zoom as u32) as f64;
@@ -57,6 +63,15 @@ pub(crate) fn tile_id(position: Position, mut zoom: u8, source_tile_size: u32) -
     TileId { x, y, zoom }
 }
 
+/// Zoom level of tiles of the given size, which cover the same area as 256 px tiles at `zoom`.
+/// Some sources provide larger tiles, effectively bundling e.g. 4 256px tiles in one 512px one,
+/// while others provide smaller ones. Walkers uses 256px internally, so the zoom level needs to be
+/// adjusted by one for every doubling (or halving) of the size.
+pub(crate) fn tile_zoom(zoom: u8, tile_size: u32) -> u8 {
+    let offset = (tile_size as f64 / TILE_SIZE as f64).log2().round() as i32;
+    (zoom as i32 - offset).clamp(0, u8::MAX as i32) as u8
+}
+
 /// Project geographical position into a 2D plane using Mercator.
 pub(crate) fn project(position: Position, zoom: f64) -> Pixels {
     let total_pixels = total_pixels(zoom);
@@ -113,6 +128,16 @@ mod tests {
             tile_id(citadel, zoom, 512)
         );
 
+        // And zooms in for smaller ones. Citadel is in the eastern half of the 256px tile.
+        assert_eq!(
+            TileId {
+                x: 585455 * 2 + 1,
+                y: 345104 * 2,
+                zoom: zoom + 1
+            },
+            tile_id(citadel, zoom, 128)
+        );
+
         // Projected tile is just its x, y multiplied by the size of tiles.
         assert_eq!(
             Pixels::new(585455. * 256., 345104. * 256.),
@@ -1,14 +1,24 @@
-use egui::DragPanButtons;
+use std::time::Duration;
+
+use egui::{Color32, DragPanButtons};
 
 pub struct Options {
     pub zoom_gesture_enabled: bool,
     pub drag_pan_buttons: DragPanButtons,
     pub zoom_speed: f64,
+    pub scroll_zoom_sensitivity: f64,
     pub double_click_to_zoom: bool,
     pub double_click_to_zoom_out: bool,
+    pub triple_click_to_follow_my_position: bool,
     pub zoom_with_ctrl: bool,
     pub panning: bool,
+    pub inertia: bool,
+    pub zoom_snap: bool,
     pub pull_to_my_position_threshold: f32,
+    pub my_position_smoothing: Option<Duration>,
+    pub keyboard_gestures: bool,
+    pub tile_overscan: u32,
+    pub background: Option<Color32>,
 }
 
 impl Default for Options {
@@ -17,11 +27,19 @@ impl Default for Options {
             zoom_gesture_enabled: true,
             drag_pan_buttons: DragPanButtons::PRIMARY,
             zoom_speed: 2.0,
+            scroll_zoom_sensitivity: 0.25,
             double_click_to_zoom: false,
             double_click_to_zoom_out: false,
+            triple_click_to_follow_my_position: false,
             zoom_with_ctrl: true,
             panning: true,
+            inertia: true,
+            zoom_snap: false,
             pull_to_my_position_threshold: 0.0,
+            my_position_smoothing: None,
+            keyboard_gestures: false,
+            tile_overscan: 0,
+            background: None,
         }
     }
 }
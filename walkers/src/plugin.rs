@@ -3,7 +3,34 @@ use egui::{Response, Ui};
 use crate::ScreenProjector;
 
 /// Plugins allow drawing custom shapes on the map. After implementing this trait for your type,
-/// you can add it to the map with [`crate::Map::with_plugin`]
+/// you can add it to the map with [`crate::Map::with_plugin`], or with
+/// [`crate::Map::with_underlay_plugin`] to have it drawn underneath the tiles.
+///
+/// Plugins are consumed by the map at each frame. To get something back from a plugin, e.g.
+/// which marker was clicked, implement this trait for a mutable reference to a state kept
+/// outside the map:
+///
+/// ```
+/// # use egui::{Response, Ui};
+/// # use walkers::{Plugin, Position, ScreenProjector};
+/// #[derive(Default)]
+/// struct ClickWatcher {
+///     clicked_at: Option<Position>,
+/// }
+///
+/// impl Plugin for &mut ClickWatcher {
+///     fn run(self: Box<Self>, _ui: &mut Ui, response: &Response, projector: &ScreenProjector) {
+///         if response.clicked() {
+///             self.clicked_at = response
+///                 .interact_pointer_pos()
+///                 .map(|pos| projector.unproject(pos));
+///         }
+///     }
+/// }
+///
+/// // Then, at each frame:
+/// // map.with_plugin(&mut click_watcher)
+/// ```
 pub trait Plugin {
     /// Function called at each frame.
     ///
@@ -14,5 +41,9 @@ pub trait Plugin {
     ///
     /// The provided [`Response`] is the response of the map widget itself and can be used to test
     /// if the mouse is hovering or clicking on the map.
+    ///
+    /// To handle clicks or drags by itself, e.g. on a marker, a plugin can claim the area with
+    /// [`Ui::interact`]. Widgets placed by plugins are on top of the map, so the map won't be
+    /// dragged nor zoomed by double click when the pointer interacts with them.
     fn run(self: Box<Self>, ui: &mut Ui, response: &Response, projector: &ScreenProjector);
 }
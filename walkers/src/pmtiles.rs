@@ -66,10 +66,7 @@ impl<P: Projection> PmTiles<P> {
             let (zoomed_tile_id, uv) = interpolate_from_lower_zoom(tile_id, zoom_candidate);
 
             if let Some(Some(tile)) = self.tiles_io.cache.get(&zoomed_tile_id) {
-                break Some(TilePiece {
-                    tile: tile.clone(),
-                    uv,
-                });
+                break Some(TilePiece::new(tile.clone(), uv));
             }
 
             // Keep zooming out until we find a donor or there is no more zoom levels.
@@ -110,6 +107,10 @@ impl<P: Projection> Tiles for PmTiles<P> {
     fn tile_size(&self) -> u32 {
         self.tile_size
     }
+
+    fn loading(&self) -> bool {
+        self.tiles_io.loading()
+    }
 }
 
 #[derive(Debug, Error)]
@@ -1,6 +1,6 @@
 //! Types and functions for working with positions.
 
-use crate::projector::Projection;
+use crate::{mercator, projector::Projection};
 use egui::Vec2;
 
 /// Geographical position with latitude and longitude.
@@ -18,6 +18,99 @@ pub fn lon_lat(lon: f64, lat: f64) -> Position {
     Position::new(lon, lat)
 }
 
+/// Initial bearing (forward azimuth) of the great circle going from one position to another,
+/// expressed in degrees clockwise from north, in `0..360` range.
+pub fn bearing_degrees(from: Position, to: Position) -> f64 {
+    let from_lat = from.y().to_radians();
+    let to_lat = to.y().to_radians();
+    let delta_lon = (to.x() - from.x()).to_radians();
+
+    let y = delta_lon.sin() * to_lat.cos();
+    let x = from_lat.cos() * to_lat.sin() - from_lat.sin() * to_lat.cos() * delta_lon.cos();
+
+    y.atan2(x).to_degrees().rem_euclid(360.)
+}
+
+/// Great circle distance between two positions, in meters, computed with the haversine formula.
+///
+/// Earth is assumed to be a sphere, which is accurate to about 0.5%.
+pub fn distance_meters(from: Position, to: Position) -> f64 {
+    let from_lat = from.y().to_radians();
+    let to_lat = to.y().to_radians();
+    let delta_lat = to_lat - from_lat;
+    let delta_lon = (to.x() - from.x()).to_radians();
+
+    let a = (delta_lat / 2.).sin().powi(2)
+        + from_lat.cos() * to_lat.cos() * (delta_lon / 2.).sin().powi(2);
+
+    2. * EARTH_RADIUS * a.sqrt().min(1.).asin()
+}
+
+/// Destination reached by travelling `distance_m` meters from the `start` along a great circle
+/// with the given initial bearing (in degrees clockwise from north).
+///
+/// Earth is assumed to be a sphere, which is accurate to about 0.5%.
+pub fn destination(start: Position, bearing_deg: f64, distance_m: f64) -> Position {
+    let lat = start.y().to_radians();
+    let lon = start.x().to_radians();
+    let bearing = bearing_deg.to_radians();
+    let angular_distance = distance_m / EARTH_RADIUS;
+
+    let destination_lat = (lat.sin() * angular_distance.cos()
+        + lat.cos() * angular_distance.sin() * bearing.cos())
+    .asin();
+    let destination_lon = lon
+        + (bearing.sin() * angular_distance.sin() * lat.cos())
+            .atan2(angular_distance.cos() - lat.sin() * destination_lat.sin());
+
+    lat_lon(
+        destination_lat.to_degrees(),
+        (destination_lon.to_degrees() + 540.).rem_euclid(360.) - 180.,
+    )
+}
+
+/// Format the position as latitude and longitude in degrees, minutes and seconds, e.g.
+/// `40°26′46″N 79°58′56″W`. Longitude is wrapped to the `-180..180` range first.
+pub fn format_dms(position: Position) -> String {
+    let position = mercator::wrap_longitude(position);
+    format!(
+        "{} {}",
+        dms(position.y(), ['N', 'S']),
+        dms(position.x(), ['E', 'W'])
+    )
+}
+
+/// Format the position as latitude and longitude in decimal degrees with given number of
+/// decimal places, e.g. `40.44611, -79.98222`. Longitude is wrapped to the `-180..180` range
+/// first.
+pub fn format_decimal(position: Position, precision: usize) -> String {
+    let position = mercator::wrap_longitude(position);
+    format!("{:.precision$}, {:.precision$}", position.y(), position.x())
+}
+
+/// Format the angle as degrees, minutes and seconds, followed by the hemisphere letter given as
+/// `[positive, negative]`.
+fn dms(degrees: f64, hemispheres: [char; 2]) -> String {
+    // Round once, so that e.g. 59.9999″ carries over to the next minute.
+    let seconds = (degrees.abs() * 3600.).round() as u64;
+    let hemisphere = if degrees < 0. && seconds > 0 {
+        hemispheres[1]
+    } else {
+        hemispheres[0]
+    };
+
+    format!(
+        "{}°{}′{}″{}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+        hemisphere
+    )
+}
+
+/// Mean radius of the Earth in meters.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
 /// Geographical [`Position`] shifted by a number of pixels on the screen.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
@@ -39,12 +132,19 @@ impl AdjustedPosition {
         }
     }
 
-    /// Calculate the real position, i.e. including the offset.
+    /// Calculate the real position, i.e. including the offset. For geographical coordinates,
+    /// the longitude wraps around when panned past the antimeridian.
     pub(crate) fn position<P: Projection + ?Sized>(&self, projection: &P) -> Position {
-        projection.pixels_to_position(
+        let position = projection.pixels_to_position(
             projection.position_to_pixels(self.position, self.zoom) - self.offset,
             self.zoom,
-        )
+        );
+
+        if projection.is_geographic() {
+            crate::mercator::wrap_longitude(position)
+        } else {
+            position
+        }
     }
 
     pub(crate) fn shift(self, offset: Vec2, zoom: f64) -> Self {
@@ -61,9 +161,13 @@ impl AdjustedPosition {
     }
 
     pub(crate) fn half_offset(self) -> Self {
+        self.scaled_offset(0.5)
+    }
+
+    pub(crate) fn scaled_offset(self, factor: f64) -> Self {
         Self {
             position: self.position,
-            offset: self.offset / 2.0,
+            offset: self.offset * factor,
             zoom: self.zoom,
         }
     }
@@ -101,6 +205,75 @@ mod tests {
         AdjustedPosition::new(lat_lon(51.0, 17.0))
     }
 
+    #[test]
+    fn bearing_to_cardinal_directions() {
+        let origin = lat_lon(0., 0.);
+        assert_relative_eq!(bearing_degrees(origin, lat_lon(1., 0.)), 0.);
+        assert_relative_eq!(bearing_degrees(origin, lat_lon(0., 1.)), 90.);
+        assert_relative_eq!(bearing_degrees(origin, lat_lon(-1., 0.)), 180.);
+        assert_relative_eq!(bearing_degrees(origin, lat_lon(0., -1.)), 270.);
+    }
+
+    #[test]
+    fn bearing_between_cities() {
+        let wroclaw = lat_lon(51.1079, 17.0385);
+        let warsaw = lat_lon(52.2297, 21.0122);
+        assert_relative_eq!(bearing_degrees(wroclaw, warsaw), 63.5, epsilon = 0.5);
+        assert_relative_eq!(bearing_degrees(warsaw, wroclaw), 246.6, epsilon = 0.5);
+    }
+
+    #[test]
+    fn distance_between_positions() {
+        let one_degree = EARTH_RADIUS * std::f64::consts::PI / 180.;
+        assert_relative_eq!(
+            distance_meters(lat_lon(0., 0.), lat_lon(0., 1.)),
+            one_degree,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            distance_meters(lat_lon(0., 179.5), lat_lon(0., -179.5)),
+            one_degree,
+            epsilon = 1e-6
+        );
+
+        let wroclaw = lat_lon(51.1079, 17.0385);
+        let warsaw = lat_lon(52.2297, 21.0122);
+        assert_relative_eq!(distance_meters(wroclaw, warsaw), 301_000., epsilon = 1000.);
+
+        let end = destination(wroclaw, 123., 5000.);
+        assert_relative_eq!(distance_meters(wroclaw, end), 5000., epsilon = 1e-6);
+    }
+
+    #[test]
+    fn destination_along_meridian_and_equator() {
+        // One degree of a great circle.
+        let one_degree = EARTH_RADIUS * std::f64::consts::PI / 180.;
+
+        let north = destination(lat_lon(0., 0.), 0., one_degree);
+        assert_relative_eq!(north.y(), 1., epsilon = 1e-9);
+        assert_relative_eq!(north.x(), 0., epsilon = 1e-9);
+
+        let east = destination(lat_lon(0., 0.), 90., one_degree);
+        assert_relative_eq!(east.y(), 0., epsilon = 1e-9);
+        assert_relative_eq!(east.x(), 1., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn destination_across_antimeridian() {
+        let one_degree = EARTH_RADIUS * std::f64::consts::PI / 180.;
+        let east = destination(lat_lon(0., 179.5), 90., one_degree);
+        assert_relative_eq!(east.x(), -179.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn destination_is_consistent_with_bearing_at_high_latitudes() {
+        let start = lat_lon(89., 45.);
+        for bearing in [0., 45., 90., 135., 180., 225., 270., 315.] {
+            let end = destination(start, bearing, 1000.);
+            assert_relative_eq!(bearing_degrees(start, end), bearing, epsilon = 1e-6);
+        }
+    }
+
     #[test]
     fn shifting_adjusted_position() {
         let position = base_adjusted_position().shift(Pixels::new(10.0, 20.0).to_vec2(), 10.0);
@@ -141,4 +314,24 @@ mod tests {
         let position = position.shift(Pixels::new(10.0, 0.0).to_vec2(), 10.0);
         assert_relative_eq!(position.offset_length(), 20.0);
     }
+
+    #[test]
+    fn formatting() {
+        let pittsburgh = lat_lon(40.44611, -79.98222);
+        assert_eq!(format_dms(pittsburgh), "40°26′46″N 79°58′56″W");
+        assert_eq!(format_decimal(pittsburgh, 5), "40.44611, -79.98222");
+
+        // Southern and eastern hemispheres, and rounding carrying over to the next minute.
+        assert_eq!(
+            format_dms(lat_lon(-33.85, 151.0166666)),
+            "33°51′0″S 151°1′0″E"
+        );
+
+        // Tiny negative values round to zero, which has no hemisphere of its own.
+        assert_eq!(format_dms(lat_lon(-0.00001, 0.)), "0°0′0″N 0°0′0″E");
+
+        // Repeated copies of the world.
+        assert_eq!(format_dms(lat_lon(0., 190.)), "0°0′0″N 170°0′0″W");
+        assert_eq!(format_decimal(lat_lon(0., -190.), 1), "0.0, 170.0");
+    }
 }
@@ -1,15 +1,13 @@
-use egui::{Pos2, Rect};
+use egui::{Pos2, Rect, Vec2};
 
-use crate::{
-    MapMemory, Position, mercator,
-    position::{Pixels, PixelsExt as _},
-};
+use crate::{MapMemory, Position, mercator, position::Pixels};
 
 /// Raw coordinate projection between world coordinates and pixel space.
 ///
 /// Implementors define how a coordinate system maps to pixel coordinates at a
-/// given zoom level. For GPS coordinates, use [`MercatorProjection`].
-/// For pre-projected coordinates, use [`ProjectedProjection`].
+/// given zoom level. For GPS coordinates, use [`MercatorProjection`], or
+/// [`PlateCarreeProjection`] for EPSG:4326 tile sources. For pre-projected coordinates, use
+/// [`ProjectedProjection`].
 pub trait Projection {
     /// Convert world coordinates to pixel coordinates at a given zoom level.
     fn position_to_pixels(&self, position: Position, zoom: f64) -> Pixels;
@@ -18,10 +16,45 @@ pub trait Projection {
     fn pixels_to_position(&self, pixels: Pixels, zoom: f64) -> Position;
 
     /// Scale factor: how many pixels correspond to one meter at this position and zoom level.
+    ///
+    /// It works for any zoom level, not only the one currently shown, e.g. to size symbols or
+    /// build a scale legend ahead of time:
+    ///
+    /// ```
+    /// # use walkers::{MercatorProjection, Projection, lon_lat};
+    /// let wroclaw = lon_lat(17.03664, 51.09916);
+    /// let scales: Vec<f32> = (10..=12)
+    ///     .map(|zoom| MercatorProjection.scale_pixel_per_meter(wroclaw, zoom as f64))
+    ///     .collect();
+    /// assert!((scales[1] / scales[0] - 2.).abs() < 1e-3);
+    /// ```
     fn scale_pixel_per_meter(&self, position: Position, zoom: f64) -> f32;
 
-    /// If it is gps or not
+    /// How many pixels correspond to one unit of the coordinates at this position and zoom
+    /// level, separately along the x and y axes. Units are whatever the positions are expressed
+    /// in, i.e. degrees for geographical coordinates.
+    ///
+    /// The default implementation measures it numerically.
+    fn scale_pixel_per_unit(&self, position: Position, zoom: f64) -> Vec2 {
+        const STEP: f64 = 1e-6;
+        let origin = self.position_to_pixels(position, zoom);
+        let x = self.position_to_pixels(Position::new(position.x() + STEP, position.y()), zoom);
+        let y = self.position_to_pixels(Position::new(position.x(), position.y() + STEP), zoom);
+        Vec2::new(
+            ((x - origin).x().abs() / STEP) as f32,
+            ((y - origin).y().abs() / STEP) as f32,
+        )
+    }
+
+    /// Whether it is the Web Mercator projection, for math specific to it, e.g. the latitudes it
+    /// is defined for.
     fn is_mercator(&self) -> bool;
+
+    /// Whether positions are longitudes and latitudes in degrees, so that the world wraps around
+    /// at the antimeridian and ends at the poles. Defaults to [`Projection::is_mercator`].
+    fn is_geographic(&self) -> bool {
+        self.is_mercator()
+    }
 }
 
 /// Web Mercator projection for GPS (lat/lon) coordinates.
@@ -38,14 +71,68 @@ impl Projection for MercatorProjection {
     }
 
     fn scale_pixel_per_meter(&self, position: Position, zoom: f64) -> f32 {
-        const EARTH_CIRCUMFERENCE: f64 = 40_075_016.686;
         let total_pixels = mercator::total_pixels(zoom);
         let pixel_per_meter_equator = total_pixels / EARTH_CIRCUMFERENCE;
         let latitude_rad = position.y().abs().to_radians();
         (pixel_per_meter_equator / latitude_rad.cos()) as f32
     }
 
+    fn scale_pixel_per_unit(&self, position: Position, zoom: f64) -> Vec2 {
+        let pixel_per_degree = mercator::total_pixels(zoom) / 360.;
+        Vec2::new(
+            pixel_per_degree as f32,
+            (pixel_per_degree / position.y().to_radians().cos()) as f32,
+        )
+    }
+
+    fn is_mercator(&self) -> bool {
+        true
+    }
+}
+
+/// Length of the equator in meters.
+const EARTH_CIRCUMFERENCE: f64 = 40_075_016.686;
+
+/// Equirectangular (plate carrée, EPSG:4326) projection for GPS (lat/lon) coordinates, where
+/// both longitude and latitude map linearly to pixels.
+///
+/// Tiles use the same grid as the OGC `WorldCRS84Quad` tile matrix set, but shifted by one zoom
+/// level: the single tile at zoom 0 spans 360°, with the world in its upper half, and zoom `n`
+/// corresponds to `WorldCRS84Quad` level `n - 1`.
+#[derive(Debug, Clone)]
+pub struct PlateCarreeProjection;
+
+impl Projection for PlateCarreeProjection {
+    fn position_to_pixels(&self, position: Position, zoom: f64) -> Pixels {
+        let total_pixels = mercator::total_pixels(zoom);
+        Pixels::new(
+            (position.x() + 180.) / 360. * total_pixels,
+            (90. - position.y()) / 360. * total_pixels,
+        )
+    }
+
+    fn pixels_to_position(&self, pixels: Pixels, zoom: f64) -> Position {
+        let total_pixels = mercator::total_pixels(zoom);
+        Position::new(
+            pixels.x() / total_pixels * 360. - 180.,
+            90. - pixels.y() / total_pixels * 360.,
+        )
+    }
+
+    /// Scale in the north-south direction. East-west, a meter gets shorter towards the poles.
+    fn scale_pixel_per_meter(&self, _position: Position, zoom: f64) -> f32 {
+        (mercator::total_pixels(zoom) / EARTH_CIRCUMFERENCE) as f32
+    }
+
+    fn scale_pixel_per_unit(&self, _position: Position, zoom: f64) -> Vec2 {
+        Vec2::splat((mercator::total_pixels(zoom) / 360.) as f32)
+    }
+
     fn is_mercator(&self) -> bool {
+        false
+    }
+
+    fn is_geographic(&self) -> bool {
         true
     }
 }
@@ -67,6 +154,13 @@ impl ProjectedProjection {
     pub fn new(center: Position, scale: f64) -> Self {
         Self { center, scale }
     }
+
+    /// Construct a projection where one screen point spans `units_per_point` world units at the
+    /// given `zoom` level. E.g., for coordinates in millimeters, `1.0` at zoom 16 makes a
+    /// millimeter one point large at that zoom level.
+    pub fn with_units_per_point(center: Position, units_per_point: f64, zoom: f64) -> Self {
+        Self::new(center, 1. / (units_per_point * 2f64.powf(zoom)))
+    }
 }
 
 impl Projection for ProjectedProjection {
@@ -85,9 +179,14 @@ impl Projection for ProjectedProjection {
         )
     }
 
-    fn scale_pixel_per_meter(&self, _position: Position, zoom: f64) -> f32 {
-        // For projected coordinates assumed to be in meters, scale is uniform.
-        (self.scale * 2f64.powf(zoom)) as f32
+    /// Assumes that coordinates are in meters. Otherwise, see
+    /// [`Projection::scale_pixel_per_unit`].
+    fn scale_pixel_per_meter(&self, position: Position, zoom: f64) -> f32 {
+        self.scale_pixel_per_unit(position, zoom).x
+    }
+
+    fn scale_pixel_per_unit(&self, _position: Position, zoom: f64) -> Vec2 {
+        Vec2::splat((self.scale * 2f64.powf(zoom)) as f32)
     }
 
     fn is_mercator(&self) -> bool {
@@ -104,11 +203,32 @@ impl Projection for ProjectedProjection {
 pub struct ScreenProjector<'a, P: Projection + ?Sized = dyn Projection> {
     pub projection: &'a P,
     pub clip_rect: Rect,
+    /// Copy of the map's memory, taken when the projector was created. The original is not
+    /// borrowed, so it can be changed while the projector is in use, but the projector does not
+    /// follow these changes.
     pub memory: MapMemory,
     pub center_projected: Pixels,
+    /// Size of the tiles of the map's bottom layer, in pixels. 256 if there are no layers.
+    pub tile_size: u32,
 }
 
 impl<'a, P: Projection + ?Sized> ScreenProjector<'a, P> {
+    /// Projector for a map shown in the `clip_rect`, centered according to the `map_memory`.
+    /// It does not need the map widget nor a running frame, so it can be used in tests or for
+    /// offscreen rendering:
+    ///
+    /// ```
+    /// # use egui::{Pos2, Rect, Vec2};
+    /// # use walkers::{MapMemory, MercatorProjection, ScreenProjector, lon_lat};
+    /// let wroclaw = lon_lat(17.03664, 51.09916);
+    /// let memory = MapMemory::new(wroclaw, 15.).expect("valid zoom");
+    /// let rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(800., 600.));
+    /// let projector = ScreenProjector::new(&MercatorProjection, rect, &memory, wroclaw);
+    ///
+    /// assert_eq!(projector.project(wroclaw), rect.center());
+    /// let position = projector.unproject(Pos2::new(100., 100.));
+    /// assert!((projector.project(position) - Pos2::new(100., 100.)).length() < 1e-3);
+    /// ```
     pub fn new(
         projection: &'a P,
         clip_rect: Rect,
@@ -122,15 +242,100 @@ impl<'a, P: Projection + ?Sized> ScreenProjector<'a, P> {
             clip_rect,
             memory: map_memory.to_owned(),
             center_projected,
+            tile_size: 256,
         }
     }
 
+    /// Set the size of the tiles, see [`ScreenProjector::tile_size`].
+    pub fn with_tile_size(mut self, tile_size: u32) -> Self {
+        self.tile_size = tile_size;
+        self
+    }
+
+    /// Current zoom, see [`MapMemory::zoom`].
+    pub fn zoom(&self) -> f64 {
+        self.memory.zoom()
+    }
+
+    /// Current zoom rounded to an integer level, see [`MapMemory::zoom_level`].
+    pub fn zoom_level(&self) -> u8 {
+        self.memory.zoom_level()
+    }
+
+    /// Zoom level of the tiles currently drawn, which is one less than
+    /// [`MapMemory::zoom_level`] for 512 px tiles, as they cover four 256 px ones, and one more for
+    /// 128 px tiles. Useful for
+    /// matching the level of detail of the base map, e.g. to thin out labels when zoomed out.
+    pub fn tile_zoom_level(&self) -> u8 {
+        mercator::tile_zoom(self.memory.zoom_level(), self.tile_size)
+    }
+
+    /// Project the position onto the screen. For geographical coordinates, the copy of the
+    /// position closest to the map's center is picked, so e.g. longitude -179° is drawn just
+    /// east of 179°.
     pub fn project(&self, position: Position) -> Pos2 {
-        let projected = self
-            .projection
-            .position_to_pixels(position, self.memory.zoom());
-        (self.clip_rect.center().to_vec2() + (projected - self.center_projected).to_vec2())
-            .to_pos2()
+        let mut offset = self.offset_from_center(position);
+
+        if self.projection.is_geographic() {
+            let world_width = mercator::total_pixels(self.memory.zoom());
+            offset.set_x(offset.x() - (offset.x() / world_width).round() * world_width);
+        }
+
+        self.offset_to_screen(offset)
+    }
+
+    /// Like [`ScreenProjector::project`], but without picking the closest copy of the position,
+    /// so e.g. longitude 190° is drawn east of 170°. Useful for lines which have their
+    /// longitudes unwrapped already.
+    pub fn project_without_wrapping(&self, position: Position) -> Pos2 {
+        self.offset_to_screen(self.offset_from_center(position))
+    }
+
+    fn offset_from_center(&self, position: Position) -> Pixels {
+        self.projection
+            .position_to_pixels(position, self.memory.zoom())
+            - self.center_projected
+    }
+
+    fn offset_to_screen(&self, offset: Pixels) -> Pos2 {
+        // Stay in f64 until the very end, as projected positions are huge at high zoom levels.
+        let center = self.clip_rect.center();
+        Pos2::new(
+            (center.x as f64 + offset.x()) as f32,
+            (center.y as f64 + offset.y()) as f32,
+        )
+    }
+
+    /// Project the position onto the screen, returning `None` if it falls outside of the
+    /// [`ScreenProjector::clip_rect`]. Useful for skipping things that are not visible.
+    pub fn project_visible(&self, position: Position) -> Option<Pos2> {
+        self.project_visible_with_margin(position, 0.)
+    }
+
+    /// Same as [`ScreenProjector::project_visible`], but the clip rect is expanded by `margin`
+    /// points, so that e.g. markers partially sticking into the view are not skipped.
+    pub fn project_visible_with_margin(&self, position: Position, margin: f32) -> Option<Pos2> {
+        let projected = self.project(position);
+        self.clip_rect
+            .expand(margin)
+            .contains(projected)
+            .then_some(projected)
+    }
+
+    /// Screen rect of the bounding box given by its north-west and south-east corners. For
+    /// geographical coordinates, the box is placed like in [`ScreenProjector::project`], and it
+    /// may cross the antimeridian, in which case its west edge has a greater longitude than the
+    /// east one.
+    pub fn project_bounds(&self, north_west: Position, mut south_east: Position) -> Rect {
+        if self.projection.is_geographic() && south_east.x() < north_west.x() {
+            south_east = Position::new(south_east.x() + 360., south_east.y());
+        }
+
+        // Keep both corners on the same copy of the world.
+        let min = self.project(north_west);
+        let shift = min - self.project_without_wrapping(north_west);
+        let max = self.project_without_wrapping(south_east) + shift;
+        Rect::from_two_pos(min, max)
     }
 
     pub fn unproject(&self, screen_position: Pos2) -> Position {
@@ -142,10 +347,31 @@ impl<'a, P: Projection + ?Sized> ScreenProjector<'a, P> {
         self.projection.pixels_to_position(Pixels::new(x, y), zoom)
     }
 
+    /// Positions of the viewport's corners, in the north-west, north-east, south-east,
+    /// south-west order.
+    pub fn viewport_corners(&self) -> [Position; 4] {
+        [
+            self.clip_rect.left_top(),
+            self.clip_rect.right_top(),
+            self.clip_rect.right_bottom(),
+            self.clip_rect.left_bottom(),
+        ]
+        .map(|corner| self.unproject(corner))
+    }
+
+    /// How many pixels correspond to one meter at the current zoom level, see
+    /// [`Projection::scale_pixel_per_meter`] for other zoom levels.
     pub fn scale_pixel_per_meter(&self, position: Position) -> f32 {
         self.projection
             .scale_pixel_per_meter(position, self.memory.zoom())
     }
+
+    /// How many pixels correspond to one unit of the coordinates, see
+    /// [`Projection::scale_pixel_per_unit`].
+    pub fn scale_pixel_per_unit(&self, position: Position) -> Vec2 {
+        self.projection
+            .scale_pixel_per_unit(position, self.memory.zoom())
+    }
 }
 
 #[allow(clippy::unwrap_used)]
@@ -164,6 +390,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn memory_can_be_changed_while_the_projector_is_in_use() {
+        let position = lon_lat(21., 52.);
+        let mut map_memory = MapMemory::new(position, 10.).unwrap();
+        let projector = ScreenProjector::new(
+            &MercatorProjection,
+            Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.)),
+            &map_memory,
+            position,
+        );
+
+        map_memory.set_zoom(12.).unwrap();
+        assert_eq!(projector.zoom(), 10.);
+        assert_eq!(projector.project(position), Pos2::new(50., 50.));
+    }
+
     #[test]
     fn test_unproject_precision() {
         let original = lon_lat(21., 52.);
@@ -220,6 +462,140 @@ mod tests {
         assert_approx_eq(original.y(), unprojected.y());
     }
 
+    #[test]
+    fn round_trip_is_accurate_to_a_fraction_of_a_pixel() {
+        let rect = Rect::from_min_size(Pos2::new(30., 40.), Vec2::new(800., 600.));
+        let screen_positions = [rect.center(), rect.left_top(), rect.right_bottom()];
+
+        // Starting with zoom where the whole world is wider than the viewport, as otherwise
+        // the closest copy of the position is projected, not the original one.
+        for zoom in [2., 5.5, 12., 18., 20., 24., 26.] {
+            let mut map_memory = MapMemory::default();
+            map_memory.set_zoom(zoom).unwrap();
+
+            for lat in [-85., -60., -30., 0., 30., 60., 80., 85.] {
+                for lon in [-179.9, -90., 0., 17.03664, 179.9] {
+                    let center = lon_lat(lon, lat);
+                    let projector =
+                        ScreenProjector::new(&MercatorProjection, rect, &map_memory, center);
+
+                    for screen_position in screen_positions {
+                        let position = projector.unproject(screen_position);
+                        let distance = projector.project(position) - screen_position;
+                        assert!(
+                            distance.length() < 0.01,
+                            "{distance:?} at {lon}, {lat}, zoom {zoom}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn closest_copy_of_position_is_projected() {
+        let mut map_memory = MapMemory::default();
+        map_memory.set_zoom(5.).unwrap();
+
+        let projector = ScreenProjector::new(
+            &MercatorProjection,
+            Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.)),
+            &map_memory,
+            lon_lat(179.9, 0.),
+        );
+
+        // Whole world is 8192 pixels wide at this zoom.
+        let just_across = projector.project(lon_lat(-179.9, 0.));
+        assert!((just_across.x - (50. + 8192. / 360. * 0.2)).abs() < 0.01);
+
+        let far_away = projector.project_without_wrapping(lon_lat(-179.9, 0.));
+        assert!((far_away.x - (50. - 8192. / 360. * 359.8)).abs() < 0.01);
+    }
+
+    #[test]
+    fn project_visible_skips_positions_outside_of_clip_rect() {
+        let center = lon_lat(21., 52.);
+
+        let mut map_memory = MapMemory::default();
+        map_memory.set_zoom(10.).unwrap();
+
+        let projector = ScreenProjector::new(
+            &MercatorProjection,
+            Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.)),
+            &map_memory,
+            center,
+        );
+
+        assert_eq!(projector.project_visible(center), Some(Pos2::new(50., 50.)));
+
+        // Roughly 70 pixels to the east at this zoom.
+        let east = lon_lat(21.1, 52.);
+        assert_eq!(projector.project_visible(east), None);
+        assert_eq!(
+            projector.project_visible_with_margin(east, 30.),
+            Some(projector.project(east))
+        );
+    }
+
+    #[test]
+    fn bounds_are_projected_to_a_rect() {
+        let mut map_memory = MapMemory::default();
+        map_memory.set_zoom(5.).unwrap();
+
+        let projector = ScreenProjector::new(
+            &MercatorProjection,
+            Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.)),
+            &map_memory,
+            lon_lat(179.9, 0.),
+        );
+
+        let rect = projector.project_bounds(lon_lat(179.8, 0.1), lon_lat(179.95, -0.1));
+        assert_eq!(rect.min, projector.project(lon_lat(179.8, 0.1)));
+        assert_eq!(rect.max, projector.project(lon_lat(179.95, -0.1)));
+
+        // Box across the antimeridian stays in one piece around the center.
+        let rect = projector.project_bounds(lon_lat(179.8, 0.1), lon_lat(-179.8, -0.1));
+        assert!((rect.width() - 8192. / 360. * 0.4).abs() < 0.01);
+        assert!(rect.contains(Pos2::new(50., 50.)));
+
+        // Corners given the other way around still make a proper rect in projected coordinates,
+        // where there is no antimeridian to cross.
+        let projection = ProjectedProjection::new(lon_lat(0., 0.), 1.);
+        let projector = ScreenProjector::new(
+            &projection,
+            Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.)),
+            &map_memory,
+            lon_lat(0., 0.),
+        );
+        let rect = projector.project_bounds(lon_lat(1., -1.), lon_lat(-1., 1.));
+        assert!(rect.is_positive());
+        assert!(rect.contains(Pos2::new(50., 50.)));
+    }
+
+    #[test]
+    fn viewport_corners_surround_the_center() {
+        let center = lon_lat(21., 52.);
+
+        let projector = ScreenProjector::new(
+            &MercatorProjection,
+            Rect::from_min_size(Pos2::ZERO, Vec2::new(200., 100.)),
+            &MapMemory::default(),
+            center,
+        );
+
+        let [nw, ne, se, sw] = projector.viewport_corners();
+
+        assert!(nw.x() < center.x() && nw.y() > center.y());
+        assert!(ne.x() > center.x() && ne.y() > center.y());
+        assert!(se.x() > center.x() && se.y() < center.y());
+        assert!(sw.x() < center.x() && sw.y() < center.y());
+
+        assert_approx_eq(nw.x(), sw.x());
+        assert_approx_eq(ne.x(), se.x());
+        assert_approx_eq(nw.y(), ne.y());
+        assert_approx_eq(sw.y(), se.y());
+    }
+
     #[test]
     fn projected_roundtrip() {
         let original = Position::new(100.0, 200.0);
@@ -241,4 +617,92 @@ mod tests {
         assert_approx_eq(original.x(), unprojected.x());
         assert_approx_eq(original.y(), unprojected.y());
     }
+
+    #[test]
+    fn projected_units_per_point() {
+        let origin = Position::new(1000., 2000.);
+        let projection = ProjectedProjection::with_units_per_point(origin, 5., 16.);
+
+        let pixels = projection.position_to_pixels(Position::new(1050., 2000.), 16.);
+        assert_approx_eq(pixels.x(), 10.);
+
+        // One zoom level less makes everything twice as small.
+        let pixels = projection.position_to_pixels(Position::new(1000., 1950.), 15.);
+        assert_approx_eq(pixels.y(), 5.);
+    }
+
+    #[test]
+    fn plate_carree_is_linear() {
+        let projection = PlateCarreeProjection;
+
+        assert_eq!(
+            projection.position_to_pixels(lon_lat(-180., 90.), 0.),
+            Pixels::new(0., 0.)
+        );
+        assert_eq!(
+            projection.position_to_pixels(lon_lat(0., 0.), 1.),
+            Pixels::new(256., 128.)
+        );
+
+        let original = lon_lat(21., 52.);
+        let unprojected =
+            projection.pixels_to_position(projection.position_to_pixels(original, 12.5), 12.5);
+        assert_approx_eq(original.x(), unprojected.x());
+        assert_approx_eq(original.y(), unprojected.y());
+    }
+
+    #[test]
+    fn scale_pixel_per_unit_matches_numerical_one() {
+        struct Numerical<P>(P);
+
+        impl<P: Projection> Projection for Numerical<P> {
+            fn position_to_pixels(&self, position: Position, zoom: f64) -> Pixels {
+                self.0.position_to_pixels(position, zoom)
+            }
+
+            fn pixels_to_position(&self, pixels: Pixels, zoom: f64) -> Position {
+                self.0.pixels_to_position(pixels, zoom)
+            }
+
+            fn scale_pixel_per_meter(&self, position: Position, zoom: f64) -> f32 {
+                self.0.scale_pixel_per_meter(position, zoom)
+            }
+
+            fn is_mercator(&self) -> bool {
+                self.0.is_mercator()
+            }
+
+            fn is_geographic(&self) -> bool {
+                self.0.is_geographic()
+            }
+        }
+
+        fn check(projection: impl Projection + Clone) {
+            let position = lon_lat(21., 52.);
+            let exact = projection.scale_pixel_per_unit(position, 10.);
+            let numerical = Numerical(projection).scale_pixel_per_unit(position, 10.);
+            assert!((exact - numerical).length() < exact.length() * 1e-3);
+        }
+
+        check(MercatorProjection);
+        check(PlateCarreeProjection);
+        check(ProjectedProjection::new(lon_lat(20., 50.), 2.));
+    }
+
+    #[test]
+    fn zoom_accessors() {
+        let mut memory = MapMemory::default();
+        memory.set_zoom(12.4).unwrap();
+        let projector = ScreenProjector::new(
+            &MercatorProjection,
+            Rect::from_min_size(Pos2::ZERO, Vec2::new(100., 100.)),
+            &memory,
+            lon_lat(0., 0.),
+        );
+
+        assert_eq!(projector.zoom(), 12.4);
+        assert_eq!(projector.zoom_level(), 12);
+        assert_eq!(projector.tile_zoom_level(), 12);
+        assert_eq!(projector.with_tile_size(512).tile_zoom_level(), 11);
+    }
 }
@@ -0,0 +1,77 @@
+use crate::TileId;
+use crate::projector::MercatorProjection;
+
+use super::{Attribution, TileSource};
+
+/// CARTO basemap styles.
+/// <https://github.com/CartoDB/basemap-styles>
+#[derive(Clone, Copy, Default)]
+pub enum CartoStyle {
+    #[default]
+    Positron,
+    PositronNoLabels,
+    DarkMatter,
+    DarkMatterNoLabels,
+    Voyager,
+    VoyagerNoLabels,
+}
+
+impl CartoStyle {
+    fn api_slug(&self) -> &'static str {
+        match self {
+            Self::Positron => "light_all",
+            Self::PositronNoLabels => "light_nolabels",
+            Self::DarkMatter => "dark_all",
+            Self::DarkMatterNoLabels => "dark_nolabels",
+            Self::Voyager => "rastertiles/voyager",
+            Self::VoyagerNoLabels => "rastertiles/voyager_nolabels",
+        }
+    }
+}
+
+/// CARTO basemaps tile source.
+/// <https://carto.com/basemaps>
+#[derive(Default)]
+pub struct Carto {
+    /// Style to use
+    pub style: CartoStyle,
+    /// Render tiles at 512x512 instead of 256x256 (@2x)
+    pub high_resolution: bool,
+}
+
+impl TileSource for Carto {
+    type Projection = MercatorProjection;
+
+    fn projection(&self) -> MercatorProjection {
+        MercatorProjection
+    }
+
+    fn tile_url(&self, tile_id: TileId) -> String {
+        // Spread the load over the subdomains, always picking the same one for a given tile so
+        // that HTTP caching works.
+        let subdomain = ["a", "b", "c", "d"][((tile_id.x + tile_id.y) % 4) as usize];
+
+        format!(
+            "https://{}.basemaps.cartocdn.com/{}/{}/{}/{}{}.png",
+            subdomain,
+            self.style.api_slug(),
+            tile_id.zoom,
+            tile_id.x,
+            tile_id.y,
+            if self.high_resolution { "@2x" } else { "" },
+        )
+    }
+
+    fn attribution(&self) -> Attribution {
+        Attribution {
+            text: "© OpenStreetMap contributors, © CARTO",
+            url: "https://carto.com/attributions",
+            logo_light: None,
+            logo_dark: None,
+        }
+    }
+
+    fn max_zoom(&self) -> u8 {
+        20
+    }
+}
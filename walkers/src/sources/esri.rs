@@ -0,0 +1,80 @@
+use crate::TileId;
+use crate::projector::MercatorProjection;
+
+use super::{Attribution, TileSource};
+
+/// Esri basemaps served by ArcGIS Online.
+#[derive(Clone, Copy, Default)]
+pub enum EsriMap {
+    #[default]
+    WorldImagery,
+    WorldTopoMap,
+    WorldStreetMap,
+}
+
+impl EsriMap {
+    fn service(&self) -> &'static str {
+        match self {
+            Self::WorldImagery => "World_Imagery",
+            Self::WorldTopoMap => "World_Topo_Map",
+            Self::WorldStreetMap => "World_Street_Map",
+        }
+    }
+}
+
+/// <https://www.arcgis.com/home/item.html?id=10df2279f9684e4a9f6a7f08febac2a9>
+pub struct Esri(pub EsriMap);
+
+impl TileSource for Esri {
+    type Projection = MercatorProjection;
+
+    fn projection(&self) -> MercatorProjection {
+        MercatorProjection
+    }
+
+    fn tile_url(&self, tile_id: TileId) -> String {
+        // ArcGIS REST API puts the row before the column.
+        format!(
+            "https://server.arcgisonline.com/ArcGIS/rest/services/{}/MapServer/tile/{}/{}/{}",
+            self.0.service(),
+            tile_id.zoom,
+            tile_id.y,
+            tile_id.x
+        )
+    }
+
+    fn attribution(&self) -> Attribution {
+        Attribution {
+            text: match self.0 {
+                EsriMap::WorldImagery => {
+                    "Powered by Esri | Source: Esri, Maxar, Earthstar Geographics, and the GIS User Community"
+                }
+                EsriMap::WorldTopoMap | EsriMap::WorldStreetMap => {
+                    "Powered by Esri | Sources: Esri, HERE, Garmin, USGS, Intermap, INCREMENT P, NRCan, Esri Japan, METI, Esri China (Hong Kong), Esri Korea, Esri (Thailand), NGCC, © OpenStreetMap contributors, and the GIS User Community"
+                }
+            },
+            url: "https://www.esri.com/",
+            logo_light: None,
+            logo_dark: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_goes_before_column() {
+        let tile_id = TileId {
+            x: 1,
+            y: 2,
+            zoom: 3,
+        };
+
+        assert_eq!(
+            Esri(EsriMap::WorldImagery).tile_url(tile_id),
+            "https://server.arcgisonline.com/ArcGIS/rest/services/World_Imagery/MapServer/tile/3/2/1"
+        );
+    }
+}
@@ -1,20 +1,28 @@
 //! Some common HTTP tile sources. Make sure you follow terms of usage of the particular source.
 
+mod carto;
+mod esri;
 mod geoportal;
 mod mapbox;
 #[cfg(feature = "mvt")]
 mod openfreemap;
 mod openstreetmap;
 mod opentopomap;
+mod stadia;
+mod thunderforest;
 
 use crate::TileId;
 use crate::projector::Projection;
+pub use carto::{Carto, CartoStyle};
+pub use esri::{Esri, EsriMap};
 pub use geoportal::Geoportal;
 pub use mapbox::{Mapbox, MapboxStyle};
 #[cfg(feature = "mvt")]
 pub use openfreemap::OpenFreeMap;
 pub use openstreetmap::OpenStreetMap;
 pub use opentopomap::{OpenTopoMap, OpenTopoServer};
+pub use stadia::{Stadia, StadiaStyle};
+pub use thunderforest::{Thunderforest, ThunderforestStyle};
 
 #[derive(Clone)]
 pub struct Attribution {
@@ -33,12 +41,49 @@ pub trait TileSource {
     fn attribution(&self) -> Attribution;
     fn projection(&self) -> Self::Projection;
 
-    /// Size of each tile, should be a multiple of 256.
+    /// Size of each tile, should be a power of two, such as 128, 256 or 512. Tiles are taken from
+    /// a zoom level lower by one for every doubling of the size, so that they cover the same area.
     fn tile_size(&self) -> u32 {
         256
     }
 
+    /// Lowest zoom level the source has tiles for. Nothing is downloaded below it.
+    fn min_zoom(&self) -> u8 {
+        0
+    }
+
+    /// Highest zoom level the source has tiles for. Above it, tiles of this level are stretched.
     fn max_zoom(&self) -> u8 {
         19
     }
 }
+
+/// Allows choosing between sources of different types at runtime, e.g. for
+/// [`crate::HttpTiles::set_source`].
+impl<T: TileSource + ?Sized> TileSource for Box<T> {
+    type Projection = T::Projection;
+
+    fn tile_url(&self, tile_id: TileId) -> String {
+        (**self).tile_url(tile_id)
+    }
+
+    fn attribution(&self) -> Attribution {
+        (**self).attribution()
+    }
+
+    fn projection(&self) -> Self::Projection {
+        (**self).projection()
+    }
+
+    fn tile_size(&self) -> u32 {
+        (**self).tile_size()
+    }
+
+    fn min_zoom(&self) -> u8 {
+        (**self).min_zoom()
+    }
+
+    fn max_zoom(&self) -> u8 {
+        (**self).max_zoom()
+    }
+}
@@ -0,0 +1,106 @@
+use crate::TileId;
+use crate::projector::MercatorProjection;
+
+use super::{Attribution, TileSource};
+
+/// Stadia Maps raster styles, including the classic Stamen ones.
+/// <https://docs.stadiamaps.com/themes/>
+#[derive(Clone, Copy, Default)]
+pub enum StadiaStyle {
+    #[default]
+    AlidadeSmooth,
+    AlidadeSmoothDark,
+    AlidadeSatellite,
+    OsmBright,
+    Outdoors,
+    StamenToner,
+    StamenTonerLite,
+    StamenTerrain,
+    StamenWatercolor,
+}
+
+impl StadiaStyle {
+    fn api_slug(&self) -> &'static str {
+        match self {
+            Self::AlidadeSmooth => "alidade_smooth",
+            Self::AlidadeSmoothDark => "alidade_smooth_dark",
+            Self::AlidadeSatellite => "alidade_satellite",
+            Self::OsmBright => "osm_bright",
+            Self::Outdoors => "outdoors",
+            Self::StamenToner => "stamen_toner",
+            Self::StamenTonerLite => "stamen_toner_lite",
+            Self::StamenTerrain => "stamen_terrain",
+            Self::StamenWatercolor => "stamen_watercolor",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::AlidadeSatellite | Self::StamenWatercolor => "jpg",
+            _ => "png",
+        }
+    }
+
+    fn stamen(&self) -> bool {
+        matches!(
+            self,
+            Self::StamenToner
+                | Self::StamenTonerLite
+                | Self::StamenTerrain
+                | Self::StamenWatercolor
+        )
+    }
+}
+
+/// Stadia Maps tile source.
+/// <https://docs.stadiamaps.com/raster/>
+#[derive(Default)]
+pub struct Stadia {
+    /// Style to use
+    pub style: StadiaStyle,
+    /// Render tiles at 512x512 instead of 256x256 (@2x)
+    pub high_resolution: bool,
+    /// Stadia Maps API key, required outside of localhost and registered domains
+    pub api_key: String,
+}
+
+impl TileSource for Stadia {
+    type Projection = MercatorProjection;
+
+    fn projection(&self) -> MercatorProjection {
+        MercatorProjection
+    }
+
+    fn tile_url(&self, tile_id: TileId) -> String {
+        format!(
+            "https://tiles.stadiamaps.com/tiles/{}/{}/{}/{}{}.{}?api_key={}",
+            self.style.api_slug(),
+            tile_id.zoom,
+            tile_id.x,
+            tile_id.y,
+            if self.high_resolution { "@2x" } else { "" },
+            self.style.extension(),
+            self.api_key
+        )
+    }
+
+    fn attribution(&self) -> Attribution {
+        Attribution {
+            text: if self.style.stamen() {
+                "© Stadia Maps, © Stamen Design, © OpenMapTiles, © OpenStreetMap contributors"
+            } else {
+                "© Stadia Maps, © OpenMapTiles, © OpenStreetMap contributors"
+            },
+            url: "https://stadiamaps.com/attribution/",
+            logo_light: None,
+            logo_dark: None,
+        }
+    }
+
+    fn max_zoom(&self) -> u8 {
+        match self.style {
+            StadiaStyle::StamenWatercolor => 16,
+            _ => 20,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::TileId;
+use crate::projector::MercatorProjection;
+
+use super::{Attribution, TileSource};
+
+/// Thunderforest map styles.
+/// <https://www.thunderforest.com/maps/>
+#[derive(Clone, Copy, Default)]
+pub enum ThunderforestStyle {
+    #[default]
+    Cycle,
+    Transport,
+    TransportDark,
+    Landscape,
+    Outdoors,
+    SpinalMap,
+    Pioneer,
+    MobileAtlas,
+    Neighbourhood,
+    Atlas,
+}
+
+impl ThunderforestStyle {
+    fn api_slug(&self) -> &'static str {
+        match self {
+            Self::Cycle => "cycle",
+            Self::Transport => "transport",
+            Self::TransportDark => "transport-dark",
+            Self::Landscape => "landscape",
+            Self::Outdoors => "outdoors",
+            Self::SpinalMap => "spinal-map",
+            Self::Pioneer => "pioneer",
+            Self::MobileAtlas => "mobile-atlas",
+            Self::Neighbourhood => "neighbourhood",
+            Self::Atlas => "atlas",
+        }
+    }
+}
+
+/// Thunderforest tile source.
+/// <https://www.thunderforest.com/docs/map-tiles-api/>
+#[derive(Default)]
+pub struct Thunderforest {
+    /// Style to use
+    pub style: ThunderforestStyle,
+    /// Render tiles at 512x512 instead of 256x256 (@2x)
+    pub high_resolution: bool,
+    /// Thunderforest API key, required
+    pub api_key: String,
+}
+
+impl TileSource for Thunderforest {
+    type Projection = MercatorProjection;
+
+    fn projection(&self) -> MercatorProjection {
+        MercatorProjection
+    }
+
+    fn tile_url(&self, tile_id: TileId) -> String {
+        format!(
+            "https://tile.thunderforest.com/{}/{}/{}/{}{}.png?apikey={}",
+            self.style.api_slug(),
+            tile_id.zoom,
+            tile_id.x,
+            tile_id.y,
+            if self.high_resolution { "@2x" } else { "" },
+            self.api_key
+        )
+    }
+
+    fn attribution(&self) -> Attribution {
+        Attribution {
+            text: "Maps © Thunderforest, Data © OpenStreetMap contributors",
+            url: "https://www.thunderforest.com/",
+            logo_light: None,
+            logo_dark: None,
+        }
+    }
+
+    fn max_zoom(&self) -> u8 {
+        22
+    }
+}
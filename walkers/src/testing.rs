@@ -0,0 +1,85 @@
+//! Helpers shared by tests of different modules.
+
+use crate::{MercatorProjection, Tile, TileId, TilePiece, Tiles, sources::Attribution};
+use egui::{Color32, ColorImage, Context, Rect, TextureHandle, pos2};
+use std::collections::{BTreeSet, HashSet};
+
+/// [`Tiles`] which records which tiles were asked for and returns them right away, so that
+/// tile selection can be tested without any IO.
+pub(crate) struct MockTiles {
+    tile_size: u32,
+    attribution: &'static str,
+    texture: TextureHandle,
+    missing: HashSet<TileId>,
+
+    /// Every call to [`Tiles::at`], in order. The same tile is asked for once for every copy of
+    /// the world it is drawn in.
+    pub requested: Vec<TileId>,
+}
+
+impl MockTiles {
+    pub fn new(tile_size: u32) -> Self {
+        let texture = Context::default().load_texture(
+            "mock tile",
+            ColorImage::filled([1, 1], Color32::RED),
+            Default::default(),
+        );
+
+        Self {
+            tile_size,
+            attribution: "mock",
+            texture,
+            missing: HashSet::new(),
+            requested: Vec::new(),
+        }
+    }
+
+    pub fn with_attribution(mut self, text: &'static str) -> Self {
+        self.attribution = text;
+        self
+    }
+
+    /// Tile which is still not available, e.g. not downloaded yet.
+    pub fn with_missing(mut self, tile_id: TileId) -> Self {
+        self.missing.insert(tile_id);
+        self
+    }
+
+    /// Distinct tiles asked for, as (zoom, x, y), which sorts nicely in assertions.
+    pub fn requested(&self) -> BTreeSet<(u8, u32, u32)> {
+        self.requested
+            .iter()
+            .map(|tile_id| (tile_id.zoom, tile_id.x, tile_id.y))
+            .collect()
+    }
+}
+
+impl Tiles for MockTiles {
+    type Projection = MercatorProjection;
+
+    fn at(&mut self, tile_id: TileId) -> Option<TilePiece> {
+        self.requested.push(tile_id);
+
+        if self.missing.contains(&tile_id) {
+            return None;
+        }
+
+        Some(TilePiece::new(
+            Tile::Raster(self.texture.clone()),
+            Rect::from_min_max(pos2(0., 0.), pos2(1., 1.)),
+        ))
+    }
+
+    fn attribution(&self) -> Attribution {
+        Attribution {
+            text: self.attribution,
+            url: "",
+            logo_light: None,
+            logo_dark: None,
+        }
+    }
+
+    fn tile_size(&self) -> u32 {
+        self.tile_size
+    }
+}
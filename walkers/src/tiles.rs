@@ -5,17 +5,19 @@ use crate::text::Text;
 #[cfg(feature = "mvt")]
 use crate::text::{OccupiedAreas, OrientedRect};
 
+use egui::emath::GuiRounding as _;
 use egui::{Color32, Context, Mesh, Rect, Vec2, pos2};
-use egui::{ColorImage, TextureHandle};
+use egui::{ColorImage, TextureHandle, TextureOptions};
 #[cfg(feature = "mvt")]
 use egui::{FontId, Shape};
 use image::{ImageError, ImageReader};
 use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
 use crate::Position;
 use crate::io::TileFactory;
-use crate::mercator::{project, tile_id, total_tiles};
+use crate::mercator::{tile_id, tile_zoom, total_pixels, total_tiles, unproject, wrap_longitude};
 use crate::position::{Pixels, PixelsExt};
 use crate::projector::Projection;
 use crate::sources::Attribution;
@@ -62,6 +64,123 @@ impl TileId {
         Pixels::new(self.x as f64 * tile_size, self.y as f64 * tile_size)
     }
 
+    /// Tile of given zoom level which contains the position, in Web Mercator. Positions beyond
+    /// the latitude range of the projection fall into the northernmost or southernmost tiles.
+    pub fn containing(position: Position, zoom: u8) -> TileId {
+        let tile_id = tile_id(wrap_longitude(position), zoom, 256);
+        let last = total_tiles(zoom) - 1;
+        TileId {
+            x: tile_id.x.min(last),
+            y: tile_id.y.min(last),
+            zoom,
+        }
+    }
+
+    /// Geographical positions of the north-west and south-east corners of the tile, in Web
+    /// Mercator.
+    pub fn bounds(&self) -> (Position, Position) {
+        let zoom = self.zoom as f64;
+        let south_east = TileId {
+            x: self.x + 1,
+            y: self.y + 1,
+            zoom: self.zoom,
+        };
+        (
+            unproject(self.project(256.), zoom),
+            unproject(south_east.project(256.), zoom),
+        )
+    }
+
+    /// Quadkey of the tile, as used e.g. by Bing Maps. Each digit picks one of the four
+    /// children of the previous tile, so its length is the zoom level.
+    /// See: <https://learn.microsoft.com/en-us/bingmaps/articles/bing-maps-tile-system>
+    pub fn to_quadkey(&self) -> String {
+        (1..=self.zoom)
+            .rev()
+            .map(|level| {
+                let mask = 1 << (level - 1);
+                let digit = u8::from(self.x & mask != 0) + 2 * u8::from(self.y & mask != 0);
+                char::from(b'0' + digit)
+            })
+            .collect()
+    }
+
+    /// Tile of the given quadkey, or `None` if it is not a valid one.
+    pub fn from_quadkey(quadkey: &str) -> Option<TileId> {
+        // Tile numbers need to fit into u32.
+        if quadkey.len() >= 32 {
+            return None;
+        }
+
+        let mut tile_id = TileId {
+            x: 0,
+            y: 0,
+            zoom: quadkey.len() as u8,
+        };
+
+        for digit in quadkey.chars() {
+            let digit = digit.to_digit(4)?;
+            tile_id.x = tile_id.x << 1 | digit & 1;
+            tile_id.y = tile_id.y << 1 | digit >> 1;
+        }
+
+        Some(tile_id)
+    }
+
+    /// Tile one zoom level lower which covers this one, or `None` at zoom 0.
+    pub fn parent(&self) -> Option<TileId> {
+        Some(TileId {
+            x: self.x / 2,
+            y: self.y / 2,
+            zoom: self.zoom.checked_sub(1)?,
+        })
+    }
+
+    /// Four tiles one zoom level higher which cover this one, in the north-west, north-east,
+    /// south-west, south-east order, or `None` if their numbers would not fit into `u32`.
+    pub fn children(&self) -> Option<[TileId; 4]> {
+        // Doubled numbers are even, so adding one to them cannot overflow.
+        let x = self.x.checked_mul(2)?;
+        let y = self.y.checked_mul(2)?;
+        let zoom = self.zoom.checked_add(1)?;
+
+        Some([(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dx, dy)| TileId {
+            x: x + dx,
+            y: y + dy,
+            zoom,
+        }))
+    }
+
+    /// Tiles surrounding this one, including the diagonal ones. Unlike [`TileId::east`] and
+    /// [`TileId::west`], these wrap around the antimeridian. There are no neighbors beyond the
+    /// northernmost and southernmost rows, and none of them is repeated at low zoom levels, where
+    /// the world is only a few tiles wide.
+    pub fn neighbors(&self) -> Vec<TileId> {
+        let total = total_tiles(self.zoom) as i64;
+        let mut neighbors = Vec::with_capacity(8);
+
+        for dy in -1..=1 {
+            let y = self.y as i64 + dy;
+            if !(0..total).contains(&y) {
+                continue;
+            }
+
+            for dx in -1..=1 {
+                let tile_id = TileId {
+                    x: (self.x as i64 + dx).rem_euclid(total) as u32,
+                    y: y as u32,
+                    zoom: self.zoom,
+                };
+
+                if tile_id != *self && !neighbors.contains(&tile_id) {
+                    neighbors.push(tile_id);
+                }
+            }
+        }
+
+        neighbors
+    }
+
     pub fn east(&self) -> Option<TileId> {
         (self.x < total_tiles(self.zoom) - 1).then_some(TileId {
             x: self.x + 1,
@@ -107,6 +226,13 @@ pub trait Tiles {
     fn at(&mut self, tile_id: TileId) -> Option<TilePiece>;
     fn attribution(&self) -> Attribution;
     fn tile_size(&self) -> u32;
+
+    /// Whether tiles requested so far are still being loaded. Checked after the map is drawn, it
+    /// tells whether the visible area is complete, e.g. before taking a screenshot. Sources
+    /// which load tiles right away return `false`, which is the default.
+    fn loading(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Clone)]
@@ -114,12 +240,27 @@ pub enum Tile {
     Raster(TextureHandle),
     #[cfg(feature = "mvt")]
     Vector(Vec<ShapeOrText>),
+    /// Tile which is known to have no data, e.g. served by a sparse overlay source as an empty
+    /// response or a fully transparent image. It draws nothing.
+    Empty,
 }
 
 impl Tile {
     /// Create a tile from raw image data. The data can be either raster image (PNG, JPEG, etc.)
-    /// or vector tile (MVT) if the `mvt` feature is enabled.
+    /// or vector tile (MVT) if the `mvt` feature is enabled. The format is recognized from the
+    /// data itself, so neither the URL extension nor the `Content-Type` header matters.
     pub fn new(image: &[u8], style: &Style, zoom: u8, ctx: &Context) -> Result<Self, TileError> {
+        Self::with_texture_options(image, style, zoom, ctx, TextureOptions::default())
+    }
+
+    /// Like [`Tile::new`], but raster images are uploaded with given texture options.
+    pub(crate) fn with_texture_options(
+        image: &[u8],
+        style: &Style,
+        zoom: u8,
+        ctx: &Context,
+        texture_options: TextureOptions,
+    ) -> Result<Self, TileError> {
         #[cfg(not(feature = "mvt"))]
         let _ = (style, zoom);
 
@@ -131,13 +272,18 @@ impl Tile {
         if reader.format().is_some() {
             log::debug!("Decoding tile as raster image.");
             let image = reader.decode()?.to_rgba8();
+            if image.pixels().all(|pixel| pixel[3] == 0) {
+                return Ok(Self::Empty);
+            }
+
+            // Decoded images have straight alpha, while egui blends premultiplied colors.
             let pixels = image.as_flat_samples();
             let image = ColorImage::from_rgba_unmultiplied(
                 [image.width() as _, image.height() as _],
                 pixels.as_slice(),
             );
 
-            Ok(Self::from_color_image(image, ctx))
+            Ok(Self::from_color_image(image, ctx, texture_options))
         } else {
             #[cfg(feature = "mvt")]
             {
@@ -157,8 +303,8 @@ impl Tile {
     }
 
     /// Load the texture from egui's [`ColorImage`].
-    fn from_color_image(color_image: ColorImage, ctx: &Context) -> Self {
-        Self::Raster(ctx.load_texture("image", color_image, Default::default()))
+    fn from_color_image(color_image: ColorImage, ctx: &Context, options: TextureOptions) -> Self {
+        Self::Raster(ctx.load_texture("image", color_image, options))
     }
 
     /// Draw the tile on the given `rect`. The `uv` parameter defines which part of the tile
@@ -167,6 +313,8 @@ impl Tile {
         match self {
             Tile::Raster(texture_handle) => {
                 let mut mesh = Mesh::with_texture(texture_handle.id());
+                // Vertex color is premultiplied too, so scaling all of its channels fades the tile
+                // out without tinting it.
                 mesh.add_rect_with_uv(rect, uv, Color32::WHITE.gamma_multiply(transparency));
                 painter.add(egui::Shape::mesh(mesh));
             }
@@ -193,6 +341,7 @@ impl Tile {
 
                 painter.extend(shapes);
             }
+            Tile::Empty => {}
         }
     }
 
@@ -237,83 +386,254 @@ impl Tile {
 pub struct TilePiece {
     pub tile: Tile,
     pub uv: Rect,
+
+    /// Opacity the piece is drawn with, on top of the layer's transparency. It is below 1.0
+    /// while a freshly loaded tile is fading in.
+    pub opacity: f32,
+
+    /// Piece of a lower zoom tile drawn underneath, so that there is no gap while this one is
+    /// not fully opaque.
+    pub placeholder: Option<Box<TilePiece>>,
 }
 
 impl TilePiece {
     pub fn new(tile: Tile, uv: Rect) -> Self {
-        Self { tile, uv }
+        Self {
+            tile,
+            uv,
+            opacity: 1.,
+            placeholder: None,
+        }
+    }
+
+    /// Set the opacity the piece is drawn with. Default is 1.0.
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Set the piece drawn underneath this one.
+    pub fn with_placeholder(mut self, placeholder: TilePiece) -> Self {
+        self.placeholder = Some(Box::new(placeholder));
+        self
+    }
+
+    fn draw(&self, painter: &egui::Painter, rect: Rect, transparency: f32) {
+        if let Some(placeholder) = &self.placeholder {
+            placeholder.draw(painter, rect, transparency);
+        }
+
+        self.tile
+            .draw(painter, rect, self.uv, transparency * self.opacity);
     }
 }
 
 pub(crate) fn draw_tiles<P: Projection>(
     painter: &egui::Painter,
+    projection: &P,
     map_center: Position,
     zoom: Zoom,
     tiles: &mut dyn Tiles<Projection = P>,
     transparency: f32,
+    overscan: u32,
 ) {
-    let mut meshes = Default::default();
-    flood_fill_tiles(
-        painter,
-        tile_id(map_center, zoom.round(), tiles.tile_size()),
-        project(map_center, zoom.into()),
+    // Tiles are asked for every frame, even if the view did not change, as that is what drives
+    // their downloads and fading in. Drawing a raster tile is just a single textured rectangle.
+    for (tile_id, tile_rect) in select_tiles(
+        projection,
+        map_center,
         zoom.into(),
-        tiles,
-        transparency,
-        &mut meshes,
-    );
-}
-
-/// Use simple [flood fill algorithm](https://en.wikipedia.org/wiki/Flood_fill) to draw tiles on the map.
-fn flood_fill_tiles<P: Projection>(
-    painter: &egui::Painter,
-    tile_id: TileId,
-    map_center_projected_position: Pixels,
-    zoom: f64,
-    tiles: &mut dyn Tiles<Projection = P>,
-    transparency: f32,
-    meshes: &mut HashSet<TileId>,
-) {
-    // We need to make up the difference between integer and floating point zoom levels.
-    let corrected_tile_size = tiles.tile_size() as f64 * 2f64.powf(zoom - zoom.round());
-    let tile_projected = tile_id.project(corrected_tile_size);
-    let tile_screen_position = painter.clip_rect().center().to_vec2()
-        + (tile_projected - map_center_projected_position).to_vec2();
-
-    if painter
-        .clip_rect()
-        .intersects(rect(tile_screen_position, corrected_tile_size))
-        && meshes.insert(tile_id)
-    {
+        tiles.tile_size(),
+        painter.clip_rect(),
+        overscan,
+    ) {
         if let Some(tile) = tiles.at(tile_id) {
-            tile.tile.draw(
+            // Snap to physical pixels, so that there are no seams between tiles on fractional
+            // scales. Neighbouring tiles share the edge, so they round to the same pixel.
+            tile.draw(
                 painter,
-                rect(tile_screen_position, corrected_tile_size),
-                tile.uv,
+                tile_rect.round_to_pixels(painter.pixels_per_point()),
                 transparency,
             )
         }
+    }
+}
 
-        for next_tile_id in [
-            tile_id.north(),
-            tile_id.east(),
-            tile_id.south(),
-            tile_id.west(),
-        ]
-        .iter()
-        .flatten()
-        {
-            flood_fill_tiles(
-                painter,
-                *next_tile_id,
-                map_center_projected_position,
-                zoom,
-                tiles,
-                transparency,
-                meshes,
+/// Tiles which cover the `clip_rect` of a map centered at `map_center`, along with screen rects
+/// they are drawn at. With `overscan`, the `clip_rect` is extended by that many tiles on each
+/// side.
+///
+/// Selection is cheap compared to drawing, so it is deliberately not cached.
+fn select_tiles<P: Projection + ?Sized>(
+    projection: &P,
+    map_center: Position,
+    zoom: f64,
+    tile_size: u32,
+    clip_rect: Rect,
+    overscan: u32,
+) -> Vec<(TileId, Rect)> {
+    let tile_screen_size = tile_size as f64 * 2f64.powf(zoom - zoom.round());
+    let clip_rect = clip_rect.expand((overscan as f64 * tile_screen_size) as f32);
+
+    let map_center_projected_position = projection.position_to_pixels(map_center, zoom);
+    let center_tile_id = tile_id_from_pixels(
+        map_center_projected_position,
+        zoom,
+        zoom.round() as u8,
+        tile_size,
+    );
+
+    world_copies(
+        center_tile_id,
+        map_center_projected_position,
+        zoom,
+        clip_rect,
+        projection.is_geographic(),
+    )
+    .into_iter()
+    .flat_map(|(start, map_center_projected_position)| {
+        flood_fill_tiles(
+            start,
+            map_center_projected_position,
+            zoom,
+            tile_size,
+            clip_rect,
+        )
+    })
+    .collect()
+}
+
+/// Tile containing the given projected position. Tiles are taken from the `zoom_level`,
+/// adjusted for sources with tiles of other size than 256 px.
+///
+/// Positions outside of the tile grid, e.g. west or north of the origin of a
+/// [`crate::ProjectedProjection`], give the closest tile instead. If that one does not intersect
+/// the clip rect, no other does.
+fn tile_id_from_pixels(pixels: Pixels, zoom: f64, zoom_level: u8, tile_size: u32) -> TileId {
+    let scale = 2f64.powf(zoom_level as f64 - zoom) / tile_size as f64;
+    let zoom = tile_zoom(zoom_level, tile_size);
+    let last = (total_tiles(zoom) - 1) as f64;
+
+    TileId {
+        x: (pixels.x() * scale).floor().clamp(0., last) as u32,
+        y: (pixels.y() * scale).floor().clamp(0., last) as u32,
+        zoom,
+    }
+}
+
+/// Copies of the world which are visible in the `clip_rect`, as the tile to start the flood fill
+/// from and the projected map center, shifted so that tiles are drawn in that copy. Without
+/// `wrap`, there is only the original one.
+fn world_copies(
+    center_tile_id: TileId,
+    map_center_projected_position: Pixels,
+    zoom: f64,
+    clip_rect: Rect,
+    wrap: bool,
+) -> Vec<(TileId, Pixels)> {
+    if !wrap {
+        return vec![(center_tile_id, map_center_projected_position)];
+    }
+
+    let world_width = total_pixels(zoom);
+    let west_edge = clip_rect.center().x as f64 - map_center_projected_position.x();
+    let copies_west = ((west_edge - clip_rect.left() as f64) / world_width).ceil() as i64;
+    let copies_east =
+        ((clip_rect.right() as f64 - west_edge - world_width) / world_width).ceil() as i64;
+
+    (-copies_west.max(0)..=copies_east.max(0))
+        .map(|copy| {
+            // Start from the column next to the original world.
+            let start = match copy {
+                0 => center_tile_id,
+                1.. => TileId {
+                    x: 0,
+                    ..center_tile_id
+                },
+                ..0 => TileId {
+                    x: total_tiles(center_tile_id.zoom) - 1,
+                    ..center_tile_id
+                },
+            };
+            let shifted = Pixels::new(
+                map_center_projected_position.x() - copy as f64 * world_width,
+                map_center_projected_position.y(),
+            );
+            (start, shifted)
+        })
+        .collect()
+}
+
+/// Use simple [flood fill algorithm](https://en.wikipedia.org/wiki/Flood_fill) to find tiles
+/// covering the `clip_rect`, starting from the `start` one.
+fn flood_fill_tiles(
+    start: TileId,
+    map_center_projected_position: Pixels,
+    zoom: f64,
+    tile_size: u32,
+    clip_rect: Rect,
+) -> Vec<(TileId, Rect)> {
+    let mut result = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack = vec![start];
+
+    while let Some(tile_id) = stack.pop() {
+        let tile_rect = tile_screen_rect(
+            tile_id,
+            map_center_projected_position,
+            zoom,
+            tile_size,
+            clip_rect,
+        );
+
+        if clip_rect.intersects(tile_rect) && visited.insert(tile_id) {
+            result.push((tile_id, tile_rect));
+            stack.extend(
+                [
+                    tile_id.north(),
+                    tile_id.east(),
+                    tile_id.south(),
+                    tile_id.west(),
+                ]
+                .into_iter()
+                .flatten(),
             );
         }
     }
+
+    result
+}
+
+/// Where on the screen the tile is drawn, for a map centered in the `clip_rect`.
+fn tile_screen_rect(
+    tile_id: TileId,
+    map_center_projected_position: Pixels,
+    zoom: f64,
+    tile_size: u32,
+    clip_rect: Rect,
+) -> Rect {
+    // We need to make up the difference between integer and floating point zoom levels.
+    let corrected_tile_size = tile_size as f64 * 2f64.powf(zoom - zoom.round());
+    let tile_projected = tile_id.project(corrected_tile_size);
+    let tile_screen_position =
+        clip_rect.center().to_vec2() + (tile_projected - map_center_projected_position).to_vec2();
+
+    rect(tile_screen_position, corrected_tile_size)
+}
+
+/// Tiles of given size which cover the `clip_rect` of a map in given `projection`, centered at
+/// `map_center`, along with screen rects they are drawn at. This is the same selection the map
+/// uses for drawing its layers, without touching any [`Tiles`], so it is useful for debugging,
+/// custom overlays and custom renderers. When the world is narrower than the `clip_rect`, the
+/// same tile is returned once for every copy of the world it is drawn in.
+pub fn visible_tiles<P: Projection + ?Sized>(
+    projection: &P,
+    map_center: Position,
+    zoom: f64,
+    tile_size: u32,
+    clip_rect: Rect,
+) -> Vec<(TileId, Rect)> {
+    select_tiles(projection, map_center, zoom, tile_size, clip_rect, 0)
 }
 
 /// Take a piece of a tile with lower zoom level and use it as a required tile.
@@ -366,23 +686,262 @@ pub(crate) fn rect(screen_position: Vec2, tile_size: f64) -> Rect {
 pub(crate) struct EguiTileFactory {
     egui_ctx: Context,
     style: Style,
+    /// Shared with the tile source, so that it can be changed after the factory is handed over
+    /// to the fetching loop.
+    texture_options: Arc<Mutex<TextureOptions>>,
 }
 
 impl EguiTileFactory {
     pub(crate) fn new(egui_ctx: Context, style: Style) -> Self {
-        Self { egui_ctx, style }
+        Self {
+            egui_ctx,
+            style,
+            texture_options: Default::default(),
+        }
+    }
+
+    pub(crate) fn texture_options(&self) -> Arc<Mutex<TextureOptions>> {
+        self.texture_options.clone()
     }
 }
 
 impl TileFactory for EguiTileFactory {
     fn create_tile(&self, data: &bytes::Bytes, zoom: u8) -> Result<Tile, TileError> {
-        Tile::new(data, &self.style, zoom, &self.egui_ctx)
+        let texture_options = self
+            .texture_options
+            .lock()
+            .map(|options| *options)
+            .unwrap_or_default();
+        Tile::with_texture_options(data, &self.style, zoom, &self.egui_ctx, texture_options)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        MercatorProjection, PlateCarreeProjection, lon_lat, mercator::project, testing::MockTiles,
+    };
+    use egui::{Pos2, RawInput};
+
+    /// Draw tiles for the map centered at `map_center` on a `size` large screen.
+    fn draw(
+        map_center: Position,
+        zoom: f64,
+        size: Vec2,
+        tiles: &mut MockTiles,
+        overscan: u32,
+    ) -> egui::FullOutput {
+        draw_scaled(map_center, zoom, size, tiles, overscan, 1.)
+    }
+
+    fn draw_scaled(
+        map_center: Position,
+        zoom: f64,
+        size: Vec2,
+        tiles: &mut MockTiles,
+        overscan: u32,
+        pixels_per_point: f32,
+    ) -> egui::FullOutput {
+        let ctx = Context::default();
+        ctx.set_pixels_per_point(pixels_per_point);
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, Vec2::splat(2000.))),
+            ..Default::default()
+        };
+        ctx.run_ui(input, |ui| {
+            let painter = ui.painter_at(Rect::from_min_size(Pos2::ZERO, size));
+            let zoom = Zoom::try_from(zoom).expect("valid zoom");
+            draw_tiles(
+                &painter,
+                &MercatorProjection,
+                map_center,
+                zoom,
+                tiles,
+                1.,
+                overscan,
+            );
+        })
+    }
+
+    #[test]
+    fn exactly_the_visible_tiles_are_drawn() {
+        let mut tiles = MockTiles::new(256);
+        draw(lon_lat(0., 0.), 3., Vec2::new(300., 200.), &mut tiles, 0);
+
+        assert_eq!(
+            tiles.requested(),
+            [(3, 3, 3), (3, 4, 3), (3, 3, 4), (3, 4, 4)].into()
+        );
+        assert_eq!(tiles.requested.len(), 4);
+    }
+
+    #[test]
+    fn visible_tiles_are_the_drawn_ones() {
+        let citadel = lon_lat(21.00027, 52.26470);
+        let size = Vec2::new(300., 200.);
+
+        for (zoom, tile_size) in [(12.4, 256), (7.6, 512), (0.5, 256)] {
+            let mut tiles = MockTiles::new(tile_size);
+            draw(citadel, zoom, size, &mut tiles, 0);
+
+            let visible: Vec<_> = visible_tiles(
+                &MercatorProjection,
+                citadel,
+                zoom,
+                tile_size,
+                Rect::from_min_size(Pos2::ZERO, size),
+            )
+            .into_iter()
+            .map(|(tile_id, _)| tile_id)
+            .collect();
+            assert_eq!(visible, tiles.requested, "{zoom}");
+        }
+    }
+
+    #[test]
+    fn overscan_adds_rings_of_tiles_around_the_visible_ones() {
+        let mut tiles = MockTiles::new(256);
+        draw(lon_lat(0., 0.), 3., Vec2::new(300., 200.), &mut tiles, 1);
+
+        let expected = (2..=5)
+            .flat_map(|x| (2..=5).map(move |y| (3, x, y)))
+            .collect();
+        assert_eq!(tiles.requested(), expected);
+    }
+
+    #[test]
+    fn tiles_are_aligned_to_physical_pixels() {
+        for pixels_per_point in [1.25, 1.5] {
+            let mut tiles = MockTiles::new(256);
+            let output = draw_scaled(
+                lon_lat(21.00027, 52.26470),
+                12.37,
+                Vec2::new(300., 200.),
+                &mut tiles,
+                0,
+                pixels_per_point,
+            );
+
+            let vertices: Vec<_> = output
+                .shapes
+                .iter()
+                .filter_map(|clipped| match &clipped.shape {
+                    egui::Shape::Mesh(mesh) => Some(mesh.vertices.clone()),
+                    _ => None,
+                })
+                .flatten()
+                .collect();
+            assert_eq!(vertices.len(), 4 * tiles.requested.len());
+
+            for vertex in vertices {
+                let physical = vertex.pos * pixels_per_point;
+                assert!(
+                    (physical - physical.round()).length() < 1e-3,
+                    "{pixels_per_point} {physical:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tiles_are_selected_around_negative_projected_coordinates() {
+        // One unit is one pixel at zoom 3.
+        let projection = crate::ProjectedProjection::new(Position::new(0., 0.), 1. / 8.);
+        let clip_rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(800., 600.));
+
+        // Map center is west and north of the origin, so only the corner of the grid is visible.
+        let tiles: std::collections::BTreeSet<_> =
+            select_tiles(&projection, Position::new(-50., 20.), 3., 256, clip_rect, 0)
+                .into_iter()
+                .map(|(tile_id, _)| (tile_id.x, tile_id.y))
+                .collect();
+        assert_eq!(tiles, [(0, 0), (1, 0), (0, 1), (1, 1)].into());
+
+        // Just beyond the eastern edge of the grid.
+        let tiles = select_tiles(
+            &projection,
+            Position::new(2098., -100.),
+            3.,
+            256,
+            clip_rect,
+            0,
+        );
+        assert!(tiles.iter().any(|(tile_id, _)| tile_id.x == 7));
+        assert!(tiles.iter().all(|(tile_id, _)| tile_id.valid()));
+
+        // Far away from the grid, there is nothing to draw.
+        assert!(
+            select_tiles(
+                &projection,
+                Position::new(-5000., 0.),
+                3.,
+                256,
+                clip_rect,
+                0
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn missing_tiles_do_not_stop_the_flood_fill() {
+        let mut tiles = MockTiles::new(256).with_missing(TileId {
+            x: 4,
+            y: 4,
+            zoom: 3,
+        });
+        draw(lon_lat(0., 0.), 3., Vec2::new(300., 200.), &mut tiles, 0);
+
+        assert_eq!(
+            tiles.requested(),
+            [(3, 3, 3), (3, 4, 3), (3, 3, 4), (3, 4, 4)].into()
+        );
+    }
+
+    #[test]
+    fn tiles_are_drawn_in_every_visible_copy_of_the_world() {
+        let mut tiles = MockTiles::new(256);
+        draw(lon_lat(0., 0.), 0., Vec2::new(800., 256.), &mut tiles, 0);
+
+        // The only tile is drawn in the original world and two copies on each side.
+        assert_eq!(tiles.requested(), [(0, 0, 0)].into());
+        assert_eq!(tiles.requested.len(), 5);
+    }
+
+    #[test]
+    fn no_tiles_beyond_the_poles_are_drawn() {
+        let mut tiles = MockTiles::new(256);
+        draw(lon_lat(10., 84.9), 2., Vec2::new(300., 300.), &mut tiles, 0);
+
+        assert!(tiles.requested.iter().all(TileId::valid));
+        assert!(tiles.requested.iter().any(|tile_id| tile_id.y == 0));
+    }
+
+    #[test]
+    fn overzoomed_tiles_are_taken_from_the_rounded_zoom() {
+        let mut tiles = MockTiles::new(256);
+        draw(
+            lon_lat(21.00027, 52.26470),
+            24.6,
+            Vec2::new(300., 200.),
+            &mut tiles,
+            0,
+        );
+
+        // Tiles are drawn larger than 256 px, so only a few of them fit.
+        assert!(
+            (4..=6).contains(&tiles.requested.len()),
+            "{:?}",
+            tiles.requested
+        );
+        assert!(
+            tiles
+                .requested
+                .iter()
+                .all(|tile_id| tile_id.zoom == 25 && tile_id.valid())
+        );
+    }
 
     #[test]
     fn test_full_rect_of_clipped_tile() {
@@ -395,6 +954,341 @@ mod tests {
         assert_eq!(full_rect.max, pos2(100.0, 100.0));
     }
 
+    fn assert_position(actual: Position, lon: f64, lat: f64) {
+        assert!((actual.x() - lon).abs() < 1e-6, "{actual:?}");
+        assert!((actual.y() - lat).abs() < 1e-6, "{actual:?}");
+    }
+
+    #[test]
+    fn tile_bounds() {
+        const MAX_LATITUDE: f64 = 85.0511287798066;
+
+        let (north_west, south_east) = TileId {
+            x: 0,
+            y: 0,
+            zoom: 0,
+        }
+        .bounds();
+        assert_position(north_west, -180., MAX_LATITUDE);
+        assert_position(south_east, 180., -MAX_LATITUDE);
+
+        let (north_west, south_east) = TileId {
+            x: 1,
+            y: 0,
+            zoom: 1,
+        }
+        .bounds();
+        assert_position(north_west, 0., MAX_LATITUDE);
+        assert_position(south_east, 180., 0.);
+    }
+
+    #[test]
+    fn tile_containing_position() {
+        let citadel = Position::new(21.00027, 52.26470);
+        let tile_id = TileId::containing(citadel, 20);
+        assert_eq!(
+            tile_id,
+            TileId {
+                x: 585455,
+                y: 345104,
+                zoom: 20
+            }
+        );
+
+        let (north_west, south_east) = tile_id.bounds();
+        assert!((north_west.x()..south_east.x()).contains(&citadel.x()));
+        assert!((south_east.y()..north_west.y()).contains(&citadel.y()));
+
+        // Edges of the world.
+        assert_eq!(
+            TileId::containing(Position::new(180., -90.), 2),
+            TileId {
+                x: 3,
+                y: 3,
+                zoom: 2
+            }
+        );
+        assert_eq!(
+            TileId::containing(Position::new(-190., 90.), 2),
+            TileId {
+                x: 3,
+                y: 0,
+                zoom: 2
+            }
+        );
+    }
+
+    #[test]
+    fn quadkeys() {
+        assert_eq!(
+            TileId {
+                x: 3,
+                y: 5,
+                zoom: 3
+            }
+            .to_quadkey(),
+            "213"
+        );
+        assert_eq!(
+            TileId {
+                x: 0,
+                y: 0,
+                zoom: 0
+            }
+            .to_quadkey(),
+            ""
+        );
+        assert_eq!(
+            TileId::from_quadkey("213"),
+            Some(TileId {
+                x: 3,
+                y: 5,
+                zoom: 3
+            })
+        );
+
+        assert_eq!(TileId::from_quadkey("214"), None);
+        assert_eq!(TileId::from_quadkey("a"), None);
+        assert_eq!(TileId::from_quadkey(&"3".repeat(32)), None);
+    }
+
+    #[test]
+    fn quadkeys_round_trip() {
+        for zoom in 0..=6 {
+            for x in 0..total_tiles(zoom) {
+                for y in 0..total_tiles(zoom) {
+                    let tile_id = TileId { x, y, zoom };
+                    assert_eq!(TileId::from_quadkey(&tile_id.to_quadkey()), Some(tile_id));
+                }
+            }
+        }
+
+        let deepest = TileId::from_quadkey(&"3".repeat(31)).expect("valid quadkey");
+        assert_eq!(deepest.x, total_tiles(31) - 1);
+        assert_eq!(deepest.to_quadkey(), "3".repeat(31));
+    }
+
+    #[test]
+    fn raster_format_is_recognized_from_the_data() {
+        let image = image::RgbImage::from_pixel(4, 4, image::Rgb([200, 100, 50]));
+        let ctx = Context::default();
+
+        for format in [image::ImageFormat::Jpeg, image::ImageFormat::Png] {
+            let mut data = Vec::new();
+            image
+                .write_to(&mut std::io::Cursor::new(&mut data), format)
+                .expect("encodable image");
+
+            let tile = Tile::new(&data, &Style::default(), 0, &ctx).expect("decodable tile");
+            assert!(matches!(tile, Tile::Raster(_)), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn semi_transparent_tiles_are_premultiplied() {
+        let image = image::RgbaImage::from_pixel(4, 4, image::Rgba([200, 100, 50, 128]));
+        let mut data = Vec::new();
+        image
+            .write_to(
+                &mut std::io::Cursor::new(&mut data),
+                image::ImageFormat::Png,
+            )
+            .expect("encodable image");
+
+        let ctx = Context::default();
+        let tile = Tile::new(&data, &Style::default(), 0, &ctx).expect("decodable tile");
+        let Tile::Raster(texture) = &tile else {
+            panic!("expected raster tile");
+        };
+
+        let output = ctx.run_ui(RawInput::default(), |ui| {
+            tile.draw(
+                ui.painter(),
+                Rect::from_min_size(Pos2::ZERO, Vec2::splat(4.)),
+                Rect::from_min_max(pos2(0., 0.), pos2(1., 1.)),
+                0.5,
+            );
+        });
+
+        // Texture is uploaded with colors multiplied by alpha.
+        let (_, delta) = output
+            .textures_delta
+            .set
+            .iter()
+            .find(|(id, _)| *id == texture.id())
+            .expect("uploaded texture");
+        let egui::ImageData::Color(uploaded) = &delta.image;
+        assert_eq!(
+            uploaded.pixels[0],
+            Color32::from_rgba_unmultiplied(200, 100, 50, 128)
+        );
+        assert_eq!(uploaded.pixels[0].to_array(), [100, 50, 25, 128]);
+
+        // And it is faded out evenly, keeping the colors premultiplied.
+        let mesh = output
+            .shapes
+            .iter()
+            .find_map(|clipped| match &clipped.shape {
+                egui::Shape::Mesh(mesh) => Some(mesh.clone()),
+                _ => None,
+            })
+            .expect("tile mesh");
+        let [r, g, b, a] = mesh.vertices[0].color.to_array();
+        assert!(r == g && g == b && b == a, "{:?}", mesh.vertices[0].color);
+    }
+
+    #[test]
+    fn visible_tiles_cover_the_clip_rect() {
+        let clip_rect = Rect::from_min_size(pos2(0., 0.), Vec2::splat(512.));
+
+        // Whole world at zoom 1 fits exactly into the clip rect.
+        let mut tiles = visible_tiles(
+            &MercatorProjection,
+            Position::new(0., 0.),
+            1.,
+            256,
+            clip_rect,
+        );
+        tiles.sort_by_key(|(tile_id, _)| (tile_id.y, tile_id.x));
+
+        assert_eq!(
+            tiles,
+            vec![
+                (
+                    TileId {
+                        x: 0,
+                        y: 0,
+                        zoom: 1
+                    },
+                    Rect::from_min_size(pos2(0., 0.), Vec2::splat(256.))
+                ),
+                (
+                    TileId {
+                        x: 1,
+                        y: 0,
+                        zoom: 1
+                    },
+                    Rect::from_min_size(pos2(256., 0.), Vec2::splat(256.))
+                ),
+                (
+                    TileId {
+                        x: 0,
+                        y: 1,
+                        zoom: 1
+                    },
+                    Rect::from_min_size(pos2(0., 256.), Vec2::splat(256.))
+                ),
+                (
+                    TileId {
+                        x: 1,
+                        y: 1,
+                        zoom: 1
+                    },
+                    Rect::from_min_size(pos2(256., 256.), Vec2::splat(256.))
+                ),
+            ]
+        );
+
+        // Only a 3x3 neighbourhood at higher zoom.
+        let tiles = visible_tiles(
+            &MercatorProjection,
+            Position::new(17., 51.),
+            10.,
+            256,
+            clip_rect,
+        );
+        assert!((4..=9).contains(&tiles.len()), "{}", tiles.len());
+        assert!(tiles.iter().all(|(tile_id, rect)| {
+            tile_id.zoom == 10 && rect.intersects(clip_rect) && rect.width() == 256.
+        }));
+    }
+
+    #[test]
+    fn visible_tiles_follow_the_projection() {
+        let clip_rect = Rect::from_min_size(pos2(0., 0.), Vec2::new(400., 300.));
+        let center_tile = |projection: &dyn Projection| {
+            visible_tiles(projection, lon_lat(10., 40.), 3., 256, clip_rect)
+                .into_iter()
+                .find(|(_, rect)| rect.contains(clip_rect.center()))
+                .map(|(tile_id, _)| (tile_id.x, tile_id.y))
+        };
+
+        assert_eq!(center_tile(&MercatorProjection), Some((4, 3)));
+        assert_eq!(center_tile(&PlateCarreeProjection), Some((4, 1)));
+    }
+
+    #[test]
+    fn world_repeats_horizontally() {
+        let clip_rect = Rect::from_min_size(pos2(0., 0.), Vec2::new(1000., 256.));
+
+        // World is 256 pixels wide at zoom 0, so it fits four times, partially.
+        let tiles = visible_tiles(
+            &MercatorProjection,
+            Position::new(10., 0.),
+            0.,
+            256,
+            clip_rect,
+        );
+        assert_eq!(tiles.len(), 5);
+        assert!(tiles.iter().all(|(tile_id, _)| tile_id.valid()));
+
+        let mut lefts: Vec<_> = tiles.iter().map(|(_, rect)| rect.left()).collect();
+        lefts.sort_by(f32::total_cmp);
+        assert!(lefts[0] <= clip_rect.left());
+        assert!(lefts[4] + 256. >= clip_rect.right());
+        assert!(
+            lefts
+                .windows(2)
+                .all(|pair| (pair[1] - pair[0] - 256.).abs() < 0.01),
+            "{lefts:?}"
+        );
+    }
+
+    #[test]
+    fn tile_from_projected_position_matches_mercator_tile() {
+        let citadel = Position::new(21.00027, 52.26470);
+
+        for (zoom, tile_size) in [
+            (20., 256),
+            (20., 512),
+            (12.4, 256),
+            (7.6, 512),
+            (12.4, 128),
+            (0., 512),
+        ] {
+            let pixels = project(citadel, zoom);
+            let tile_zoom = zoom.round() as u8;
+            assert_eq!(
+                tile_id_from_pixels(pixels, zoom, tile_zoom, tile_size),
+                tile_id(citadel, tile_zoom, tile_size)
+            );
+        }
+    }
+
+    #[test]
+    fn large_tiles_are_drawn_where_their_children_would_be() {
+        let citadel = Position::new(21.00027, 52.26470);
+        let clip_rect = Rect::from_min_size(pos2(0., 0.), Vec2::new(400., 300.));
+
+        for zoom in [3., 12.4, 7.6, 15.5] {
+            let center = project(citadel, zoom);
+            let large = tile_id_from_pixels(center, zoom, zoom.round() as u8, 512);
+            let large_rect = tile_screen_rect(large, center, zoom, 512, clip_rect);
+
+            let children = large
+                .children()
+                .expect("children of a valid tile")
+                .map(|child| tile_screen_rect(child, center, zoom, 256, clip_rect));
+            let union = children
+                .iter()
+                .fold(Rect::NOTHING, |union, rect| union.union(*rect));
+
+            assert!((large_rect.min - union.min).length() < 0.01, "{zoom}");
+            assert!((large_rect.max - union.max).length() < 0.01, "{zoom}");
+            assert!(large_rect.contains(clip_rect.center()), "{zoom}");
+        }
+    }
+
     #[test]
     fn tile_id_cannot_go_beyond_limits() {
         // There is only one tile at zoom 0.
@@ -437,4 +1331,124 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn tile_id_parent_and_children() {
+        let tile_id = TileId {
+            x: 3,
+            y: 5,
+            zoom: 3,
+        };
+
+        assert_eq!(
+            tile_id.parent(),
+            Some(TileId {
+                x: 1,
+                y: 2,
+                zoom: 2
+            })
+        );
+        assert_eq!(
+            TileId {
+                x: 0,
+                y: 0,
+                zoom: 0
+            }
+            .parent(),
+            None
+        );
+
+        let children = tile_id.children().expect("children of a valid tile");
+        assert_eq!(
+            children[0],
+            TileId {
+                x: 6,
+                y: 10,
+                zoom: 4
+            }
+        );
+        assert_eq!(
+            children[3],
+            TileId {
+                x: 7,
+                y: 11,
+                zoom: 4
+            }
+        );
+        for child in children {
+            assert_eq!(child.parent(), Some(tile_id));
+        }
+
+        let last = u32::MAX / 2;
+        assert!(
+            TileId {
+                x: last,
+                y: last,
+                zoom: 31
+            }
+            .children()
+            .is_some()
+        );
+        assert_eq!(
+            TileId {
+                x: last + 1,
+                y: 0,
+                zoom: 31
+            }
+            .children(),
+            None
+        );
+        assert_eq!(
+            TileId {
+                x: 0,
+                y: last + 1,
+                zoom: 31
+            }
+            .children(),
+            None
+        );
+        assert_eq!(
+            TileId {
+                x: 0,
+                y: 0,
+                zoom: u8::MAX
+            }
+            .children(),
+            None
+        );
+    }
+
+    #[test]
+    fn tile_id_neighbors() {
+        let neighbors = |x, y, zoom| {
+            let mut neighbors: Vec<_> = TileId { x, y, zoom }
+                .neighbors()
+                .into_iter()
+                .map(|tile_id| (tile_id.x, tile_id.y))
+                .collect();
+            neighbors.sort();
+            neighbors
+        };
+
+        assert_eq!(
+            neighbors(1, 1, 2),
+            [
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 2),
+                (2, 0),
+                (2, 1),
+                (2, 2)
+            ]
+        );
+
+        // Wraps around the antimeridian, but not the poles.
+        assert_eq!(neighbors(0, 0, 2), [(0, 1), (1, 0), (1, 1), (3, 0), (3, 1)]);
+
+        // No duplicates, when the world is narrow.
+        assert_eq!(neighbors(0, 0, 1), [(0, 1), (1, 0), (1, 1)]);
+        assert_eq!(neighbors(0, 0, 0), []);
+    }
 }
@@ -1,6 +1,18 @@
-#[derive(thiserror::Error, Debug, PartialEq, Eq)]
-#[error("invalid zoom level")]
-pub struct InvalidZoom;
+/// Zoom level outside of the supported range.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq)]
+#[error("invalid zoom level {requested}, must be between {min} and {max}")]
+pub struct InvalidZoom {
+    /// Zoom level which was attempted.
+    pub requested: f64,
+    /// Minimum supported zoom level.
+    pub min: f64,
+    /// Maximum supported zoom level.
+    pub max: f64,
+}
+
+const MIN_ZOOM: f64 = 0.;
+// The upper limit is artificial. Should it be removed altogether?
+const MAX_ZOOM: f64 = 26.;
 
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
@@ -10,9 +22,12 @@ impl TryFrom<f64> for Zoom {
     type Error = InvalidZoom;
 
     fn try_from(value: f64) -> Result<Self, Self::Error> {
-        // The upper limit is artificial. Should it be removed altogether?
-        if !(0. ..=26.).contains(&value) {
-            Err(InvalidZoom)
+        if !(MIN_ZOOM..=MAX_ZOOM).contains(&value) {
+            Err(InvalidZoom {
+                requested: value,
+                min: MIN_ZOOM,
+                max: MAX_ZOOM,
+            })
         } else {
             Ok(Self(value))
         }
@@ -65,7 +80,14 @@ mod tests {
     fn test_constructing_zoom() {
         assert_eq!(16, Zoom::default().round());
         assert_eq!(26, Zoom::try_from(26.).unwrap().round());
-        assert_eq!(InvalidZoom, Zoom::try_from(27.).unwrap_err());
+        assert_eq!(
+            InvalidZoom {
+                requested: 27.,
+                min: 0.,
+                max: 26.
+            },
+            Zoom::try_from(27.).unwrap_err()
+        );
     }
 
     #[test]
@@ -73,7 +95,7 @@ mod tests {
         let mut zoom = Zoom::try_from(25.).unwrap();
         assert!(zoom.zoom_in().is_ok());
         assert_eq!(26, zoom.round());
-        assert_eq!(Err(InvalidZoom), zoom.zoom_in());
+        assert_eq!(27., zoom.zoom_in().unwrap_err().requested);
     }
 
     #[test]
@@ -81,6 +103,14 @@ mod tests {
         let mut zoom = Zoom::try_from(1.).unwrap();
         assert!(zoom.zoom_out().is_ok());
         assert_eq!(0, zoom.round());
-        assert_eq!(Err(InvalidZoom), zoom.zoom_out());
+        assert_eq!(-1., zoom.zoom_out().unwrap_err().requested);
+    }
+
+    #[test]
+    fn invalid_zoom_message() {
+        assert_eq!(
+            "invalid zoom level 30, must be between 0 and 26",
+            Zoom::try_from(30.).unwrap_err().to_string()
+        );
     }
 }
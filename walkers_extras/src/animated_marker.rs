@@ -0,0 +1,189 @@
+use egui::{Color32, Response, Stroke, Ui, Vec2};
+use walkers::{Plugin, Position, ScreenProjector, bearing_degrees, lon_lat};
+
+/// [`Plugin`] which draws a marker moving along a recorded track, e.g. for a GPS track playback.
+/// Position between the samples is interpolated linearly.
+///
+/// The marker is recreated each frame, like other plugins, with [`AnimatedMarker::time`]
+/// advanced by the application, e.g. by [`egui::InputState::stable_dt`] while playing.
+#[derive(Clone)]
+pub struct AnimatedMarker {
+    /// Positions along with their timestamps, in seconds, sorted by time.
+    pub samples: Vec<(Position, f64)>,
+
+    /// Current playback time, in the same units as the timestamps.
+    pub time: f64,
+
+    /// Whether the playback is running, in which case repaints are requested until the end of
+    /// the track is reached.
+    pub playing: bool,
+
+    /// Radius of the marker, in screen points.
+    pub radius: f32,
+
+    /// Fill color of the marker.
+    pub color: Color32,
+
+    /// Whether to draw an arrow pointing in the direction of movement.
+    pub heading: bool,
+}
+
+impl AnimatedMarker {
+    pub fn new(samples: Vec<(Position, f64)>, time: f64) -> Self {
+        Self {
+            samples,
+            time,
+            playing: false,
+            radius: 6.,
+            color: Color32::from_rgb(0, 120, 255),
+            heading: true,
+        }
+    }
+
+    /// Set whether the playback is running. Default is `false`.
+    pub fn playing(mut self, playing: bool) -> Self {
+        self.playing = playing;
+        self
+    }
+
+    /// Interpolated position at the current time. Before the first sample and after the last
+    /// one, the marker stays at the respective end of the track. `None` if there are no samples.
+    pub fn position(&self) -> Option<Position> {
+        let (from, to, t) = self.segment()?;
+        // Go the short way around across the antimeridian.
+        let delta_lon = (to.x() - from.x() + 180.).rem_euclid(360.) - 180.;
+        let lon = from.x() + delta_lon * t;
+        Some(lon_lat(
+            (lon + 180.).rem_euclid(360.) - 180.,
+            from.y() + (to.y() - from.y()) * t,
+        ))
+    }
+
+    /// Direction of movement at the current time, in degrees clockwise from north. `None` if
+    /// there are less than two distinct samples.
+    pub fn bearing(&self) -> Option<f64> {
+        let (from, to, _) = self.segment()?;
+        (from != to).then(|| bearing_degrees(from, to))
+    }
+
+    /// Samples surrounding the current time, and how far between them it is, from 0 to 1.
+    fn segment(&self) -> Option<(Position, Position, f64)> {
+        let first = self.samples.first()?;
+        let last = self.samples.last()?;
+
+        if self.samples.len() == 1 {
+            return Some((first.0, first.0, 0.));
+        }
+
+        // Index of the first sample after the current time, but at least the second one, so
+        // that direction is known before the start too.
+        let next = self
+            .samples
+            .partition_point(|(_, time)| *time <= self.time)
+            .clamp(1, self.samples.len() - 1);
+        let (from, from_time) = self.samples[next - 1];
+        let (to, to_time) = self.samples[next];
+
+        let t = if self.time <= first.1 {
+            0.
+        } else if self.time >= last.1 {
+            1.
+        } else if to_time > from_time {
+            (self.time - from_time) / (to_time - from_time)
+        } else {
+            1.
+        };
+
+        Some((from, to, t))
+    }
+}
+
+impl Plugin for AnimatedMarker {
+    fn run(self: Box<Self>, ui: &mut Ui, _response: &Response, projector: &ScreenProjector) {
+        let Some(position) = self.position() else {
+            return;
+        };
+
+        if self.playing && self.samples.last().is_some_and(|(_, end)| self.time < *end) {
+            ui.ctx().request_repaint();
+        }
+
+        let Some(center) = projector.project_visible_with_margin(position, 2. * self.radius) else {
+            return;
+        };
+
+        let painter = ui.painter();
+
+        if let Some(bearing) = self.bearing().filter(|_| self.heading) {
+            let direction = Vec2::angled((bearing as f32 - 90.).to_radians());
+            let side = direction.rot90() * self.radius * 0.8;
+            let tip = center + direction * self.radius * 2.2;
+            let base = center + direction * self.radius * 0.5;
+            painter.add(egui::Shape::convex_polygon(
+                vec![tip, base + side, base - side],
+                self.color,
+                Stroke::NONE,
+            ));
+        }
+
+        painter.circle(
+            center,
+            self.radius,
+            self.color,
+            Stroke::new(2., Color32::WHITE),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(time: f64) -> AnimatedMarker {
+        AnimatedMarker::new(
+            vec![
+                (lon_lat(0., 0.), 0.),
+                (lon_lat(10., 0.), 10.),
+                (lon_lat(10., 10.), 20.),
+            ],
+            time,
+        )
+    }
+
+    fn assert_position(marker: AnimatedMarker, lon: f64, lat: f64) {
+        let position = marker.position().expect("samples are not empty");
+        assert!((position.x() - lon).abs() < 1e-9, "{position:?}");
+        assert!((position.y() - lat).abs() < 1e-9, "{position:?}");
+    }
+
+    #[test]
+    fn position_is_interpolated() {
+        assert_position(marker(-5.), 0., 0.);
+        assert_position(marker(0.), 0., 0.);
+        assert_position(marker(2.5), 2.5, 0.);
+        assert_position(marker(10.), 10., 0.);
+        assert_position(marker(15.), 10., 5.);
+        assert_position(marker(25.), 10., 10.);
+
+        assert!(AnimatedMarker::new(Vec::new(), 0.).position().is_none());
+        assert_position(AnimatedMarker::new(vec![(lon_lat(1., 2.), 5.)], 0.), 1., 2.);
+    }
+
+    #[test]
+    fn bearing_follows_the_segment() {
+        assert!((marker(5.).bearing().expect("moving") - 90.).abs() < 1e-6);
+        assert!(marker(15.).bearing().expect("moving").abs() < 1e-6);
+        assert!(
+            AnimatedMarker::new(vec![(lon_lat(1., 2.), 5.)], 0.)
+                .bearing()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn crossing_the_antimeridian() {
+        let marker =
+            AnimatedMarker::new(vec![(lon_lat(179., 0.), 0.), (lon_lat(-179., 0.), 2.)], 1.5);
+        assert_position(marker, -179.5, 0.);
+    }
+}
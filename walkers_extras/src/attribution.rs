@@ -0,0 +1,87 @@
+use egui::{Align, Align2, Frame, Image, Layout, Response, Ui, UiBuilder};
+use walkers::{Plugin, ScreenProjector, sources::Attribution};
+
+/// [`Plugin`] which shows attributions of the tile sources in a corner of the map. Most of the
+/// providers require it. Attributions are typically taken from [`walkers::Tiles::attribution`].
+pub struct AttributionOverlay {
+    attributions: Vec<Attribution>,
+    anchor: Align2,
+    max_width: f32,
+}
+
+impl AttributionOverlay {
+    pub fn new(attributions: Vec<Attribution>) -> Self {
+        Self {
+            attributions,
+            anchor: Align2::RIGHT_BOTTOM,
+            max_width: 300.,
+        }
+    }
+
+    /// Corner of the map to show attributions in. Default is the bottom right one.
+    pub fn with_anchor(mut self, anchor: Align2) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Maximum width of the overlay. Longer texts are wrapped. Default is 300 points.
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+        self.max_width = max_width;
+        self
+    }
+}
+
+impl Plugin for AttributionOverlay {
+    fn run(self: Box<Self>, ui: &mut Ui, response: &Response, _projector: &ScreenProjector) {
+        if self.attributions.is_empty() {
+            return;
+        }
+
+        let rect = response.rect.shrink(4.);
+        let layout = if self.anchor.y() == Align::Max {
+            Layout::bottom_up(self.anchor.x())
+        } else {
+            Layout::top_down(self.anchor.x())
+        };
+
+        ui.scope_builder(UiBuilder::new().max_rect(rect).layout(layout), |ui| {
+            ui.set_max_width(self.max_width.min(rect.width()));
+
+            Frame::new()
+                .fill(ui.visuals().extreme_bg_color.gamma_multiply(0.8))
+                .corner_radius(4.)
+                .inner_margin(4.)
+                .show(ui, |ui| {
+                    ui.with_layout(Layout::top_down(Align::Min), |ui| {
+                        for attribution in &self.attributions {
+                            show_attribution(ui, attribution);
+                        }
+                    });
+                });
+        });
+    }
+}
+
+fn show_attribution(ui: &mut Ui, attribution: &Attribution) {
+    // Light logo is meant to be shown on a dark background and vice versa.
+    let logo = if ui.visuals().dark_mode {
+        attribution.logo_light.as_ref()
+    } else {
+        attribution.logo_dark.as_ref()
+    };
+
+    ui.horizontal_wrapped(|ui| {
+        if let Some(logo) = logo {
+            ui.add(Image::new(logo.clone()).max_height(20.).max_width(60.));
+        }
+
+        if attribution.url.is_empty() {
+            ui.small(attribution.text);
+        } else {
+            ui.hyperlink_to(
+                egui::RichText::new(attribution.text).small(),
+                attribution.url,
+            );
+        }
+    });
+}
@@ -0,0 +1,141 @@
+use egui::{Color32, Response, Stroke, Ui};
+use walkers::{Plugin, Position, ScreenProjector, destination};
+
+use crate::polygon::draw_polygon;
+
+/// [`Plugin`] which draws a circle of a given radius on the ground, e.g. a range ring.
+///
+/// Unlike a circle drawn in screen space, it is a true geodesic circle, therefore it gets
+/// distorted by the map's projection the same way the map is.
+#[derive(Clone)]
+pub struct Circle {
+    /// Center of the circle.
+    pub center: Position,
+
+    /// Radius in meters.
+    pub radius: f64,
+
+    /// Number of segments the circle is approximated with.
+    pub segments: usize,
+
+    /// Outline of the circle.
+    pub stroke: Stroke,
+
+    /// Fill color, typically a translucent one.
+    pub fill: Color32,
+}
+
+impl Circle {
+    pub fn new(center: Position, radius: f64, stroke: Stroke, fill: Color32) -> Self {
+        Self {
+            center,
+            radius,
+            segments: 64,
+            stroke,
+            fill,
+        }
+    }
+
+    /// Set the number of segments the circle is approximated with. Default is 64.
+    pub fn with_segments(mut self, segments: usize) -> Self {
+        self.segments = segments;
+        self
+    }
+}
+
+impl Plugin for Circle {
+    fn run(self: Box<Self>, ui: &mut Ui, _response: &Response, projector: &ScreenProjector) {
+        let ring = ring(
+            self.center,
+            self.radius,
+            self.segments,
+            projector.projection.is_geographic(),
+        );
+        draw_polygon(ui.painter(), projector, &ring, &[], self.fill, self.stroke);
+    }
+}
+
+/// Sample points of the circle. For projected (non geographical) coordinates, the circle is
+/// simply drawn in the plane.
+fn ring(center: Position, radius: f64, segments: usize, geographical: bool) -> Vec<Position> {
+    let segments = segments.max(3);
+
+    (0..segments)
+        .map(|n| 360. * n as f64 / segments as f64)
+        .map(|bearing| {
+            if geographical {
+                destination(center, bearing, radius)
+            } else {
+                let bearing = bearing.to_radians();
+                Position::new(
+                    center.x() + radius * bearing.sin(),
+                    center.y() + radius * bearing.cos(),
+                )
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{Pos2, RawInput, Rect, Vec2};
+    use walkers::{Map, MapMemory, PlateCarreeProjection, bearing_degrees, lat_lon, lon_lat};
+
+    #[test]
+    fn geodesic_ring_surrounds_the_center() {
+        let center = lat_lon(78.22, 15.65);
+        let ring = ring(center, 10_000., 8, true);
+
+        assert_eq!(ring.len(), 8);
+        for (n, point) in ring.iter().enumerate() {
+            let difference = bearing_degrees(center, *point) - 45. * n as f64;
+            assert!(((difference + 180.).rem_euclid(360.) - 180.).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn planar_ring() {
+        let ring = ring(Position::new(10., 20.), 5., 4, false);
+        let expected = [(10., 25.), (15., 20.), (10., 15.), (5., 20.)];
+
+        for (point, (x, y)) in ring.iter().zip(expected) {
+            assert!((point.x() - x).abs() < 1e-9);
+            assert!((point.y() - y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn geodesic_circle_on_plate_carree_map() {
+        let ctx = egui::Context::default();
+        let center = lon_lat(0., 0.);
+        let mut memory = MapMemory::new(center, 10.).expect("valid zoom");
+
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, Vec2::new(400., 300.))),
+            ..Default::default()
+        };
+        let output = ctx.run_ui(input, |ui| {
+            ui.add(
+                Map::new(PlateCarreeProjection, &mut memory, center).with_plugin(Circle::new(
+                    center,
+                    10_000.,
+                    Stroke::new(1., Color32::RED),
+                    Color32::TRANSPARENT,
+                )),
+            );
+        });
+
+        let bounds = output.shapes.iter().fold(Rect::NOTHING, |bounds, clipped| {
+            bounds.union(clipped.shape.visual_bounding_rect())
+        });
+
+        // 10 km is about 0.09°, which is about 65 px at zoom 10.
+        assert!((bounds.width() - 131.).abs() < 3., "{bounds:?}");
+        assert!((bounds.height() - 131.).abs() < 3., "{bounds:?}");
+        assert!(
+            (bounds.center() - Pos2::new(200., 150.)).length() < 1.,
+            "{bounds:?}"
+        );
+    }
+}
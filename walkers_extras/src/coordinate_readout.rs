@@ -0,0 +1,109 @@
+use egui::{Align, Align2, Frame, Layout, Response, RichText, Ui, UiBuilder};
+use walkers::{Plugin, Position, ScreenProjector, format_decimal, format_dms};
+
+/// How [`CoordinateReadout`] formats the position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CoordinateFormat {
+    /// Latitude and longitude in decimal degrees, with given number of decimal places.
+    Decimal { precision: usize },
+    /// Latitude and longitude in degrees, minutes and seconds, e.g. `40°26′46″N 79°58′56″W`.
+    Dms,
+    /// Plain x and y, with given number of decimal places. Meant for maps in local coordinates.
+    Local { precision: usize },
+}
+
+impl Default for CoordinateFormat {
+    fn default() -> Self {
+        Self::Decimal { precision: 5 }
+    }
+}
+
+impl CoordinateFormat {
+    fn format(&self, position: Position) -> String {
+        match *self {
+            Self::Decimal { precision } => format_decimal(position, precision),
+            Self::Dms => format_dms(position),
+            Self::Local { precision } => {
+                format!("{:.precision$}, {:.precision$}", position.x(), position.y())
+            }
+        }
+    }
+}
+
+/// [`Plugin`] which shows the position under the mouse pointer in a corner of the map.
+pub struct CoordinateReadout {
+    format: CoordinateFormat,
+    anchor: Align2,
+}
+
+impl CoordinateReadout {
+    pub fn new(format: CoordinateFormat) -> Self {
+        Self {
+            format,
+            anchor: Align2::LEFT_BOTTOM,
+        }
+    }
+
+    /// Corner of the map to show the readout in. Default is the bottom left one, so that it does
+    /// not collide with [`crate::AttributionOverlay`].
+    pub fn with_anchor(mut self, anchor: Align2) -> Self {
+        self.anchor = anchor;
+        self
+    }
+}
+
+impl Default for CoordinateReadout {
+    fn default() -> Self {
+        Self::new(CoordinateFormat::default())
+    }
+}
+
+impl Plugin for CoordinateReadout {
+    fn run(self: Box<Self>, ui: &mut Ui, response: &Response, projector: &ScreenProjector) {
+        let Some(hover_pos) = response.hover_pos() else {
+            return;
+        };
+
+        let text = self.format.format(projector.unproject(hover_pos));
+        let rect = response.rect.shrink(4.);
+        let layout = if self.anchor.y() == Align::Max {
+            Layout::bottom_up(self.anchor.x())
+        } else {
+            Layout::top_down(self.anchor.x())
+        };
+
+        ui.scope_builder(UiBuilder::new().max_rect(rect).layout(layout), |ui| {
+            Frame::new()
+                .fill(ui.visuals().extreme_bg_color.gamma_multiply(0.8))
+                .corner_radius(4.)
+                .inner_margin(4.)
+                .show(ui, |ui| {
+                    ui.label(RichText::new(text).monospace());
+                });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use walkers::lon_lat;
+
+    #[test]
+    fn formats() {
+        let pittsburgh = lon_lat(-79.98222, 40.44611);
+
+        assert_eq!(
+            CoordinateFormat::default().format(pittsburgh),
+            "40.44611, -79.98222"
+        );
+        assert_eq!(
+            CoordinateFormat::Dms.format(pittsburgh),
+            "40°26′46″N 79°58′56″W"
+        );
+        assert_eq!(
+            CoordinateFormat::Local { precision: 1 }.format(lon_lat(1200., -3.25)),
+            "1200.0, -3.2"
+        );
+    }
+}
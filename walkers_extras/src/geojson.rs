@@ -1,19 +1,24 @@
 use std::collections::HashMap;
 
-use egui::Ui;
+use egui::{Color32, Response, Stroke, Ui};
 use geo::MapCoords;
 use geo::geometry::Coord;
-use geojson::{Feature as GeoJsonFeature, GeoJson};
-use log::warn;
+use geojson::{Feature as GeoJsonFeature, FeatureCollection};
+use log::{debug, warn};
 use rstar::primitives::{GeomWithData, Rectangle};
 use rstar::{AABB, RTree};
-use walkers::{Context, Layer, Position, ScreenProjector, Style, render_line};
+use walkers::{Context, Layer, Plugin, Position, ScreenProjector, Style, render_line};
+
+use crate::polygon::draw_polygon;
+use crate::polyline::draw_polyline;
 
 struct Feature {
     geometry: walkers::Geometry<f32>,
     properties: HashMap<String, walkers::Value>,
 }
 
+/// GeoJSON features drawn with a MapLibre [`Style`], the same way as vector tiles. For simple
+/// per-feature colors, use the [`GeoJson`] plugin instead.
 pub struct GeoJsonLayer {
     /// R-tree indexing the bounding rectangles of all features.
     rtree: RTree<GeomWithData<Rectangle<[f64; 2]>, Feature>>,
@@ -21,7 +26,7 @@ pub struct GeoJsonLayer {
 }
 
 impl GeoJsonLayer {
-    pub fn new(geojson: GeoJson, style: Style) -> Self {
+    pub fn new(geojson: geojson::GeoJson, style: Style) -> Self {
         let mut indexed = Vec::new();
 
         visit_features(&geojson, |feature| {
@@ -92,6 +97,200 @@ impl GeoJsonLayer {
     }
 }
 
+/// Visual style of a feature drawn by the [`GeoJson`] plugin.
+#[derive(Clone, Debug)]
+pub struct GeoJsonStyle {
+    /// Lines and outlines of polygons and points.
+    pub stroke: Stroke,
+
+    /// Fill of polygons and points.
+    pub fill: Color32,
+
+    /// Radius of points, in pixels.
+    pub point_radius: f32,
+}
+
+impl Default for GeoJsonStyle {
+    fn default() -> Self {
+        let color = Color32::from_rgb(0x33, 0x88, 0xff);
+        Self {
+            stroke: Stroke::new(2., color),
+            fill: color.gamma_multiply(0.2),
+            point_radius: 5.,
+        }
+    }
+}
+
+/// [`Plugin`] which draws points, lines and polygons of a GeoJSON feature collection. Each
+/// feature is styled by the provided callback, which can, for example, look at its properties.
+///
+/// Use it for dropping a dataset onto the map with simple per-feature colors. For styling
+/// features with MapLibre style layers, like vector tiles are, use [`GeoJsonLayer`] instead.
+///
+/// Geometries are converted once, when the plugin is created, so keep it between frames and add
+/// it to the map by a reference:
+///
+/// ```
+/// # use walkers::{Map, MapMemory, MercatorProjection, lon_lat};
+/// # use walkers_extras::{GeoJson, GeoJsonStyle};
+/// fn update(
+///     ui: &mut egui::Ui,
+///     map_memory: &mut MapMemory,
+///     geojson: &GeoJson<impl Fn(&geojson::Feature) -> GeoJsonStyle>,
+/// ) {
+///     ui.add(
+///         Map::new(MercatorProjection, map_memory, lon_lat(17.03664, 51.09916))
+///             .with_plugin(geojson),
+///     );
+/// }
+/// ```
+pub struct GeoJson<F>
+where
+    F: Fn(&GeoJsonFeature) -> GeoJsonStyle,
+{
+    features: Vec<(GeoJsonFeature, geo::Geometry<f64>)>,
+    style: F,
+}
+
+impl<F> GeoJson<F>
+where
+    F: Fn(&GeoJsonFeature) -> GeoJsonStyle,
+{
+    pub fn new(features: FeatureCollection, style: F) -> Self {
+        let features = features
+            .features
+            .into_iter()
+            .filter_map(|feature| {
+                let geometry = feature.geometry.as_ref()?;
+                match geo::Geometry::<f64>::try_from(geometry) {
+                    Ok(geometry) => Some((feature, geometry)),
+                    Err(err) => {
+                        debug!("Skipping GeoJSON geometry: {err}");
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        Self { features, style }
+    }
+}
+
+impl<F> Plugin for &GeoJson<F>
+where
+    F: Fn(&GeoJsonFeature) -> GeoJsonStyle,
+{
+    fn run(self: Box<Self>, ui: &mut Ui, _response: &Response, projector: &ScreenProjector) {
+        let painter = ui.painter();
+
+        for (feature, geometry) in &self.features {
+            draw_geometry(painter, projector, geometry, &(self.style)(feature));
+        }
+    }
+}
+
+impl<F> Plugin for GeoJson<F>
+where
+    F: Fn(&GeoJsonFeature) -> GeoJsonStyle,
+{
+    fn run(self: Box<Self>, ui: &mut Ui, response: &Response, projector: &ScreenProjector) {
+        Box::new(&*self).run(ui, response, projector);
+    }
+}
+
+fn draw_geometry(
+    painter: &egui::Painter,
+    projector: &ScreenProjector,
+    geometry: &geo::Geometry<f64>,
+    style: &GeoJsonStyle,
+) {
+    match geometry {
+        geo::Geometry::Point(point) => draw_point(painter, projector, *point, style),
+        geo::Geometry::MultiPoint(multi_point) => {
+            for point in multi_point {
+                draw_point(painter, projector, *point, style);
+            }
+        }
+        geo::Geometry::Line(line) => draw_polyline(
+            painter,
+            projector,
+            &[line.start_point(), line.end_point()],
+            style.stroke,
+        ),
+        geo::Geometry::LineString(line_string) => {
+            draw_line_string(painter, projector, line_string, style)
+        }
+        geo::Geometry::MultiLineString(multi_line_string) => {
+            for line_string in multi_line_string {
+                draw_line_string(painter, projector, line_string, style);
+            }
+        }
+        geo::Geometry::Polygon(polygon) => draw_geo_polygon(painter, projector, polygon, style),
+        geo::Geometry::MultiPolygon(multi_polygon) => {
+            for polygon in multi_polygon {
+                draw_geo_polygon(painter, projector, polygon, style);
+            }
+        }
+        geo::Geometry::Rect(rect) => {
+            draw_geo_polygon(painter, projector, &rect.to_polygon(), style)
+        }
+        geo::Geometry::Triangle(triangle) => {
+            draw_geo_polygon(painter, projector, &triangle.to_polygon(), style)
+        }
+        geo::Geometry::GeometryCollection(collection) => {
+            for geometry in collection {
+                draw_geometry(painter, projector, geometry, style);
+            }
+        }
+    }
+}
+
+fn draw_point(
+    painter: &egui::Painter,
+    projector: &ScreenProjector,
+    position: Position,
+    style: &GeoJsonStyle,
+) {
+    if let Some(center) =
+        projector.project_visible_with_margin(position, style.point_radius + style.stroke.width)
+    {
+        painter.circle(center, style.point_radius, style.fill, style.stroke);
+    }
+}
+
+fn draw_line_string(
+    painter: &egui::Painter,
+    projector: &ScreenProjector,
+    line_string: &geo::LineString<f64>,
+    style: &GeoJsonStyle,
+) {
+    let points: Vec<_> = line_string.points().collect();
+    draw_polyline(painter, projector, &points, style.stroke);
+}
+
+fn draw_geo_polygon(
+    painter: &egui::Painter,
+    projector: &ScreenProjector,
+    polygon: &geo::Polygon<f64>,
+    style: &GeoJsonStyle,
+) {
+    let exterior: Vec<_> = polygon.exterior().points().collect();
+    let interiors: Vec<Vec<_>> = polygon
+        .interiors()
+        .iter()
+        .map(|interior| interior.points().collect())
+        .collect();
+
+    draw_polygon(
+        painter,
+        projector,
+        &exterior,
+        &interiors,
+        style.fill,
+        style.stroke,
+    );
+}
+
 /// Compute the geographic bounding rectangle of a geometry (coordinates are lon/lat).
 fn bounding_rect(geometry: &walkers::Geometry<f32>) -> Rectangle<[f64; 2]> {
     use geo::CoordsIter;
@@ -140,14 +339,106 @@ fn project_geometry(
     })
 }
 
-fn visit_features(geojson: &GeoJson, mut visitor: impl FnMut(&GeoJsonFeature)) {
+fn visit_features(geojson: &geojson::GeoJson, mut visitor: impl FnMut(&GeoJsonFeature)) {
     match geojson {
-        GeoJson::Geometry(_) => warn!("Top-level Geometry is not supported"),
-        GeoJson::Feature(feature) => visitor(feature),
-        GeoJson::FeatureCollection(feature_collection) => {
+        geojson::GeoJson::Geometry(_) => warn!("Top-level Geometry is not supported"),
+        geojson::GeoJson::Feature(feature) => visitor(feature),
+        geojson::GeoJson::FeatureCollection(feature_collection) => {
             for feature in &feature_collection.features {
                 visitor(feature);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{Pos2, RawInput, Rect, Shape, Vec2, epaint::ColorMode};
+    use walkers::{Map, MapMemory, MercatorProjection, lon_lat};
+
+    const FEATURES: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "properties": { "color": "red" },
+                "geometry": { "type": "Point", "coordinates": [17.0, 51.0] }
+            },
+            {
+                "type": "Feature",
+                "properties": { "color": "green" },
+                "geometry": {
+                    "type": "LineString",
+                    "coordinates": [[16.99, 51.0], [17.01, 51.01]]
+                }
+            },
+            {
+                "type": "Feature",
+                "properties": { "color": "blue" },
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[16.99, 50.99], [17.01, 50.99], [17.0, 50.995], [16.99, 50.99]]]
+                }
+            },
+            { "type": "Feature", "properties": null, "geometry": null }
+        ]
+    }"#;
+
+    #[test]
+    fn points_lines_and_polygons_are_drawn() {
+        let features: FeatureCollection = FEATURES.parse().expect("valid GeoJSON");
+        let geojson = GeoJson::new(features, |feature| {
+            let color = match feature.property("color").and_then(|color| color.as_str()) {
+                Some("red") => Color32::RED,
+                Some("green") => Color32::GREEN,
+                _ => Color32::BLUE,
+            };
+            GeoJsonStyle {
+                stroke: Stroke::new(2., color),
+                fill: color,
+                point_radius: 5.,
+            }
+        });
+        assert_eq!(geojson.features.len(), 3);
+
+        let ctx = egui::Context::default();
+        let center = lon_lat(17., 51.);
+        let mut memory = MapMemory::new(center, 14.).expect("valid zoom");
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, Vec2::new(400., 300.))),
+            ..Default::default()
+        };
+        let output = ctx.run_ui(input, |ui| {
+            ui.add(Map::new(MercatorProjection, &mut memory, center).with_plugin(&geojson));
+        });
+        let shapes: Vec<_> = output.shapes.iter().map(|clipped| &clipped.shape).collect();
+
+        // Point in the middle of the map.
+        assert!(shapes.iter().any(|shape| matches!(
+            shape,
+            Shape::Circle(circle)
+                if circle.fill == Color32::RED && (circle.center - Pos2::new(200., 150.)).length() < 1.
+        )));
+
+        // Line going north-east.
+        assert!(shapes.iter().any(|shape| matches!(
+            shape,
+            Shape::Path(path)
+                if path.stroke.color == ColorMode::Solid(Color32::GREEN)
+                    && path.points.len() == 2
+                    && path.points[0].x < path.points[1].x
+                    && path.points[0].y > path.points[1].y
+        )));
+
+        // Polygon, filled and outlined.
+        assert!(shapes.iter().any(|shape| matches!(
+            shape,
+            Shape::Mesh(mesh) if mesh.vertices.iter().all(|vertex| vertex.color == Color32::BLUE)
+        )));
+        assert!(shapes.iter().any(|shape| matches!(
+            shape,
+            Shape::Path(path) if path.stroke.color == ColorMode::Solid(Color32::BLUE)
+        )));
+    }
+}
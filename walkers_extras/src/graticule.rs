@@ -0,0 +1,174 @@
+use egui::{Align2, Color32, FontId, Response, Stroke, Ui, vec2};
+use walkers::{Plugin, ScreenProjector, lon_lat};
+
+/// Grid spacings in degrees, from the coarsest to the finest. Below that, 1-2-5 decimal steps are
+/// used.
+const SPACINGS: [f64; 8] = [90., 45., 30., 15., 10., 5., 2., 1.];
+
+/// Parallels are not drawn past this latitude, where Web Mercator goes to infinity.
+const MAX_LATITUDE: f64 = 85.;
+
+/// [`Plugin`] which draws lines of latitude and longitude with labels at the edges of the map.
+/// Spacing of the lines adapts to the zoom level.
+pub struct Graticule {
+    stroke: Stroke,
+    min_spacing: f32,
+}
+
+impl Graticule {
+    pub fn new() -> Self {
+        Self {
+            stroke: Stroke::new(1., Color32::GRAY.gamma_multiply(0.7)),
+            min_spacing: 100.,
+        }
+    }
+
+    /// Stroke of the lines and color of labels. Default is a thin gray line.
+    pub fn with_stroke(mut self, stroke: Stroke) -> Self {
+        self.stroke = stroke;
+        self
+    }
+
+    /// Minimal distance between lines at the center of the map, in screen points. Default is 100.
+    pub fn with_min_spacing(mut self, min_spacing: f32) -> Self {
+        self.min_spacing = min_spacing;
+        self
+    }
+}
+
+impl Default for Graticule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugin for Graticule {
+    fn run(self: Box<Self>, ui: &mut Ui, _response: &Response, projector: &ScreenProjector) {
+        let rect = projector.clip_rect;
+        let center = projector.unproject(rect.center());
+        let scale = projector.scale_pixel_per_unit(center);
+        let lon_spacing = spacing(self.min_spacing as f64 / scale.x as f64);
+        let lat_spacing = spacing(self.min_spacing as f64 / scale.y as f64);
+
+        // Longitudes are not wrapped here, so that lines continue across the antimeridian.
+        let corners = projector.viewport_corners();
+        let (min_lon, max_lon) = min_max(corners.iter().map(|corner| corner.x()));
+        let (min_lat, max_lat) = min_max(corners.iter().map(|corner| corner.y()));
+        let min_lat = min_lat.max(-MAX_LATITUDE);
+        let max_lat = max_lat.min(MAX_LATITUDE);
+
+        let painter = ui.painter();
+        let font = FontId::monospace(11.);
+
+        for lon in steps(min_lon, max_lon, lon_spacing) {
+            let top = projector.project_without_wrapping(lon_lat(lon, max_lat));
+            let bottom = projector.project_without_wrapping(lon_lat(lon, min_lat));
+            painter.line_segment([top, bottom], self.stroke);
+            painter.text(
+                egui::pos2(top.x, rect.top()) + vec2(2., 2.),
+                Align2::LEFT_TOP,
+                label(lon, lon_spacing, ['E', 'W']),
+                font.clone(),
+                self.stroke.color,
+            );
+        }
+
+        for lat in steps(min_lat, max_lat, lat_spacing) {
+            let left = projector.project_without_wrapping(lon_lat(min_lon, lat));
+            let right = projector.project_without_wrapping(lon_lat(max_lon, lat));
+            painter.line_segment([left, right], self.stroke);
+            painter.text(
+                egui::pos2(rect.left(), left.y) + vec2(2., -2.),
+                Align2::LEFT_BOTTOM,
+                label(lat, lat_spacing, ['N', 'S']),
+                font.clone(),
+                self.stroke.color,
+            );
+        }
+    }
+}
+
+fn min_max(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    })
+}
+
+/// Smallest grid spacing, in degrees, not smaller than `min_degrees`, or the coarsest one.
+fn spacing(min_degrees: f64) -> f64 {
+    let decimal = (1..=6).flat_map(|decimals| {
+        let divisor = 10f64.powi(decimals);
+        [5., 2., 1.].map(|mantissa| mantissa / divisor)
+    });
+
+    SPACINGS
+        .into_iter()
+        .chain(decimal)
+        .take_while(|&spacing| spacing >= min_degrees)
+        .last()
+        .unwrap_or(SPACINGS[0])
+}
+
+/// Multiples of `spacing` within `min..=max`.
+fn steps(min: f64, max: f64, spacing: f64) -> impl Iterator<Item = f64> {
+    let first = (min / spacing).ceil() as i64;
+    let last = (max / spacing).floor() as i64;
+    (first..=last).map(move |n| n as f64 * spacing)
+}
+
+/// Label of the line at `degrees`, with as many decimal places as the `spacing` needs and the
+/// hemisphere letter given as `[positive, negative]`.
+fn label(degrees: f64, spacing: f64, hemispheres: [char; 2]) -> String {
+    let degrees = (degrees + 180.).rem_euclid(360.) - 180.;
+    let decimals = (0..6)
+        .find(|decimals| {
+            let scaled = spacing * 10f64.powi(*decimals as i32);
+            (scaled - scaled.round()).abs() < 1e-6
+        })
+        .unwrap_or(6);
+    let text = format!("{:.decimals$}°", degrees.abs());
+
+    if degrees.abs() < spacing / 2. || (degrees.abs() - 180.).abs() < spacing / 2. {
+        text
+    } else if degrees < 0. {
+        format!("{text}{}", hemispheres[1])
+    } else {
+        format!("{text}{}", hemispheres[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spacing_adapts_to_zoom() {
+        assert_eq!(spacing(100.), 90.);
+        assert_eq!(spacing(40.), 45.);
+        assert_eq!(spacing(7.), 10.);
+        assert_eq!(spacing(1.), 1.);
+        assert_eq!(spacing(0.3), 0.5);
+        assert_eq!(spacing(0.15), 0.2);
+        assert_eq!(spacing(0.01), 0.01);
+        assert_eq!(spacing(0.004), 0.005);
+    }
+
+    #[test]
+    fn steps_cover_the_range() {
+        assert_eq!(
+            steps(-12., 21., 10.).collect::<Vec<_>>(),
+            [-10., 0., 10., 20.]
+        );
+        assert_eq!(steps(175., 195., 10.).collect::<Vec<_>>(), [180., 190.]);
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(label(10., 10., ['E', 'W']), "10°E");
+        assert_eq!(label(-0.5, 0.5, ['N', 'S']), "0.5°S");
+        assert_eq!(label(0., 1., ['E', 'W']), "0°");
+        assert_eq!(label(180., 10., ['E', 'W']), "180°");
+        // Lines past the antimeridian.
+        assert_eq!(label(190., 10., ['E', 'W']), "170°W");
+    }
+}
@@ -0,0 +1,110 @@
+use egui::{Color32, Mesh, Response, Shape, TextureId, Ui, epaint::Vertex, pos2};
+use walkers::{Plugin, Position, ScreenProjector, lon_lat};
+
+use crate::polyline::unwrap_longitudes;
+
+/// [`Plugin`] which draws a georeferenced image, e.g. a floor plan or a weather radar frame,
+/// stretched between the given corners.
+///
+/// The image is drawn as two triangles in screen space, so it follows the map only as far as
+/// the projection is close to linear within the image. That is the case unless the image spans
+/// large areas in Web Mercator.
+#[derive(Clone)]
+pub struct ImageOverlay {
+    texture: TextureId,
+    corners: [Position; 4],
+    tint: Color32,
+}
+
+impl ImageOverlay {
+    /// Image with its corners placed at given positions, in the north-west, north-east,
+    /// south-east, south-west order, i.e. clockwise starting from the image's top left. Corners
+    /// do not need to be axis-aligned, so the image can be rotated or skewed.
+    pub fn new(texture: TextureId, corners: [Position; 4]) -> Self {
+        Self {
+            texture,
+            corners,
+            tint: Color32::WHITE,
+        }
+    }
+
+    /// Image covering the bounding box given by its north-west and south-east corners.
+    pub fn from_bounds(texture: TextureId, north_west: Position, south_east: Position) -> Self {
+        Self::new(
+            texture,
+            [
+                north_west,
+                lon_lat(south_east.x(), north_west.y()),
+                south_east,
+                lon_lat(north_west.x(), south_east.y()),
+            ],
+        )
+    }
+
+    /// Color the image is multiplied by. Default is white, i.e. the image is drawn as is. Use
+    /// e.g. `Color32::WHITE.gamma_multiply(0.5)` to make it semi-transparent.
+    pub fn with_tint(mut self, tint: Color32) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    fn mesh(&self, projector: &ScreenProjector) -> Mesh {
+        // Corners across the antimeridian are kept together, on the copy of the world closest
+        // to the center.
+        let corners = unwrap_longitudes(&self.corners, projector);
+        let uvs = [pos2(0., 0.), pos2(1., 0.), pos2(1., 1.), pos2(0., 1.)];
+
+        let mut mesh = Mesh::with_texture(self.texture);
+        for (corner, uv) in corners.iter().zip(uvs) {
+            mesh.vertices.push(Vertex {
+                pos: projector.project_without_wrapping(*corner),
+                uv,
+                color: self.tint,
+            });
+        }
+        mesh.add_triangle(0, 1, 2);
+        mesh.add_triangle(0, 2, 3);
+        mesh
+    }
+}
+
+impl Plugin for ImageOverlay {
+    fn run(self: Box<Self>, ui: &mut Ui, _response: &Response, projector: &ScreenProjector) {
+        let mesh = self.mesh(projector);
+        if ui.clip_rect().intersects(mesh.calc_bounds()) {
+            ui.painter().add(Shape::mesh(mesh));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{Pos2, Rect, Vec2};
+    use walkers::{MapMemory, MercatorProjection, Projection};
+
+    #[test]
+    fn corners_are_projected_onto_the_same_world_copy() {
+        let center = lon_lat(179.9, 0.);
+        let memory = MapMemory::new(center, 10.).expect("valid zoom");
+        let projector = ScreenProjector::new(
+            &MercatorProjection as &dyn Projection,
+            Rect::from_min_size(Pos2::ZERO, Vec2::new(400., 300.)),
+            &memory,
+            center,
+        );
+
+        // Image spanning the antimeridian.
+        let overlay = ImageOverlay::from_bounds(
+            TextureId::default(),
+            lon_lat(179.8, 0.1),
+            lon_lat(-179.8, -0.1),
+        );
+        let mesh = overlay.mesh(&projector);
+
+        let [nw, ne, se, sw] = [0, 1, 2, 3].map(|i| mesh.vertices[i].pos);
+        assert!(nw.x < ne.x && sw.x < se.x);
+        assert!(nw.y < sw.y && ne.y < se.y);
+        assert!(mesh.calc_bounds().contains(Pos2::new(200., 150.)));
+    }
+}
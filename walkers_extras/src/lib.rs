@@ -1,13 +1,35 @@
 //! Extra functionalities that can be used with the map.
 
+mod animated_marker;
+mod attribution;
+mod circle;
+mod coordinate_readout;
 mod geojson;
+mod graticule;
+mod image_overlay;
 mod kml;
 mod labeled_symbol;
+mod measure_tool;
+mod minimap;
 mod places;
+mod polygon;
+mod polyline;
+mod tile_grid;
 
-pub use geojson::GeoJsonLayer;
+pub use animated_marker::AnimatedMarker;
+pub use attribution::AttributionOverlay;
+pub use circle::Circle;
+pub use coordinate_readout::{CoordinateFormat, CoordinateReadout};
+pub use geojson::{GeoJson, GeoJsonLayer, GeoJsonStyle};
+pub use graticule::Graticule;
+pub use image_overlay::ImageOverlay;
 pub use kml::KmlLayer;
 pub use labeled_symbol::{
     LabeledSymbol, LabeledSymbolGroup, LabeledSymbolGroupStyle, LabeledSymbolStyle, Symbol,
 };
+pub use measure_tool::MeasureTool;
+pub use minimap::MiniMap;
 pub use places::{Group, GroupedPlaces, GroupedPlacesTree, Place, Places};
+pub use polygon::Polygon;
+pub use polyline::{LineWidth, Polyline};
+pub use tile_grid::TileGrid;
@@ -0,0 +1,173 @@
+use egui::{Align2, Color32, FontId, Response, Stroke, Ui};
+use walkers::{Plugin, Position, ScreenProjector, distance_meters};
+
+use crate::polyline::{draw_polyline, unwrap_longitudes};
+
+/// [`Plugin`] which measures distance along a line made by clicking on the map. Each segment is
+/// labeled with its length, and the last point with the total one.
+///
+/// Points are kept between frames, so the tool is added to the map by a mutable reference:
+///
+/// ```
+/// # use walkers::{Map, MapMemory, MercatorProjection, lon_lat};
+/// # use walkers_extras::MeasureTool;
+/// fn update(ui: &mut egui::Ui, map_memory: &mut MapMemory, measure_tool: &mut MeasureTool) {
+///     ui.add(
+///         Map::new(MercatorProjection, map_memory, lon_lat(17.03664, 51.09916))
+///             .with_plugin(measure_tool),
+///     );
+/// }
+/// ```
+pub struct MeasureTool {
+    points: Vec<Position>,
+    stroke: Stroke,
+}
+
+impl MeasureTool {
+    pub fn new() -> Self {
+        Self {
+            points: Vec::new(),
+            stroke: Stroke::new(3., Color32::from_rgb(255, 80, 0)),
+        }
+    }
+
+    /// Stroke of the line and color of the points. Default is a thick orange line.
+    pub fn with_stroke(mut self, stroke: Stroke) -> Self {
+        self.stroke = stroke;
+        self
+    }
+
+    /// Points clicked so far.
+    pub fn points(&self) -> &[Position] {
+        &self.points
+    }
+
+    /// Add a point, the same as clicking on the map does.
+    pub fn push(&mut self, position: Position) {
+        self.points.push(position);
+    }
+
+    /// Remove the last point, returning it.
+    pub fn remove_last(&mut self) -> Option<Position> {
+        self.points.pop()
+    }
+
+    /// Remove all points, to start measuring from scratch.
+    pub fn reset(&mut self) {
+        self.points.clear();
+    }
+
+    /// Total length of the line, in meters.
+    pub fn total_distance(&self) -> f64 {
+        self.points
+            .windows(2)
+            .map(|segment| distance_meters(segment[0], segment[1]))
+            .sum()
+    }
+}
+
+impl Default for MeasureTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugin for &mut MeasureTool {
+    fn run(self: Box<Self>, ui: &mut Ui, response: &Response, projector: &ScreenProjector) {
+        if response.clicked()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            self.push(projector.unproject(pos));
+        }
+
+        let painter = ui.painter();
+        draw_polyline(painter, projector, &self.points, self.stroke);
+
+        let projected: Vec<_> = unwrap_longitudes(&self.points, projector)
+            .into_iter()
+            .map(|position| projector.project_without_wrapping(position))
+            .collect();
+        let font = FontId::proportional(12.);
+
+        for (segment, screen) in self.points.windows(2).zip(projected.windows(2)) {
+            let midpoint = screen[0] + (screen[1] - screen[0]) / 2.;
+            if projector.clip_rect.contains(midpoint) {
+                label(
+                    ui,
+                    midpoint,
+                    format_distance(distance_meters(segment[0], segment[1])),
+                    font.clone(),
+                );
+            }
+        }
+
+        for point in &projected {
+            painter.circle(
+                *point,
+                4.,
+                self.stroke.color,
+                Stroke::new(1.5, Color32::WHITE),
+            );
+        }
+
+        if let Some(last) = projected.last().filter(|_| projected.len() > 2) {
+            label(
+                ui,
+                *last + egui::vec2(0., -10.),
+                format!("Total: {}", format_distance(self.total_distance())),
+                FontId::proportional(14.),
+            );
+        }
+    }
+}
+
+/// Text with a halo, so that it is readable on any map.
+fn label(ui: &Ui, pos: egui::Pos2, text: String, font: FontId) {
+    let painter = ui.painter();
+    let galley = painter.layout_no_wrap(text, font, Color32::BLACK);
+    let rect = Align2::CENTER_BOTTOM.anchor_size(pos, galley.size());
+    painter.rect_filled(rect.expand(2.), 3., Color32::WHITE.gamma_multiply(0.8));
+    painter.galley(rect.min, galley, Color32::BLACK);
+}
+
+/// Distance in meters, or kilometers if it is long enough.
+fn format_distance(meters: f64) -> String {
+    if meters < 1000. {
+        format!("{meters:.0} m")
+    } else if meters < 100_000. {
+        format!("{:.2} km", meters / 1000.)
+    } else {
+        format!("{:.0} km", meters / 1000.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use walkers::lat_lon;
+
+    #[test]
+    fn distances() {
+        assert_eq!(format_distance(12.3), "12 m");
+        assert_eq!(format_distance(1234.), "1.23 km");
+        assert_eq!(format_distance(301_456.), "301 km");
+
+        let mut tool = MeasureTool::new();
+        assert_eq!(tool.total_distance(), 0.);
+
+        tool.push(lat_lon(0., 0.));
+        tool.push(lat_lon(0., 1.));
+        tool.push(lat_lon(1., 1.));
+        assert!(
+            (tool.total_distance() - 2. * 111_195.).abs() < 1.,
+            "{}",
+            tool.total_distance()
+        );
+
+        assert_eq!(tool.remove_last(), Some(lat_lon(1., 1.)));
+        assert!((tool.total_distance() - 111_195.).abs() < 1.);
+
+        tool.reset();
+        assert!(tool.points().is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+use egui::{Align2, Response, Stroke, StrokeKind, Ui, UiBuilder, Vec2, vec2};
+use walkers::{Map, MapMemory, Plugin, Position, Projection, ScreenProjector, Tiles};
+
+/// [`Plugin`] which shows a small overview map in a corner of the main one, with a rectangle
+/// marking the area visible in the main map. Clicking the overview tells where the main map
+/// should be moved, see [`MiniMap::clicked`].
+///
+/// Layers of the main map are borrowed while plugins run, so the overview needs its own
+/// [`Tiles`], e.g. another `HttpTiles` of the same source.
+///
+/// ```
+/// # use walkers::{HttpTiles, Map, MapMemory, MercatorProjection, lon_lat};
+/// # use walkers_extras::MiniMap;
+/// fn update(
+///     ui: &mut egui::Ui,
+///     tiles: &mut HttpTiles<MercatorProjection>,
+///     overview_tiles: &mut HttpTiles<MercatorProjection>,
+///     map_memory: &mut MapMemory,
+/// ) {
+///     let mut minimap = MiniMap::new(MercatorProjection, overview_tiles);
+///     ui.add(
+///         Map::new(MercatorProjection, map_memory, lon_lat(17.03664, 51.09916))
+///             .with_layer(tiles, 1.0)
+///             .with_plugin(&mut minimap),
+///     );
+///
+///     if let Some(position) = minimap.clicked() {
+///         map_memory.center_at(position);
+///     }
+/// }
+/// ```
+pub struct MiniMap<'a, P: Projection + 'static> {
+    projection: P,
+    tiles: &'a mut dyn Tiles<Projection = P>,
+    size: Vec2,
+    anchor: Align2,
+    zoom_difference: f64,
+    stroke: Stroke,
+    clicked: Option<Position>,
+}
+
+impl<'a, P: Projection + Clone + 'static> MiniMap<'a, P> {
+    pub fn new(projection: P, tiles: &'a mut dyn Tiles<Projection = P>) -> Self {
+        Self {
+            projection,
+            tiles,
+            size: vec2(200., 150.),
+            anchor: Align2::RIGHT_TOP,
+            zoom_difference: 4.,
+            stroke: Stroke::new(2., egui::Color32::from_rgb(255, 80, 0)),
+            clicked: None,
+        }
+    }
+
+    /// Size of the overview, in screen points. Default is 200x150.
+    pub fn with_size(mut self, size: Vec2) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Corner of the main map to show the overview in. Default is the top right one, so that it
+    /// does not collide with [`crate::AttributionOverlay`].
+    pub fn with_anchor(mut self, anchor: Align2) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// How many zoom levels the overview is zoomed out compared to the main map. Default is 4.
+    pub fn with_zoom_difference(mut self, zoom_difference: f64) -> Self {
+        self.zoom_difference = zoom_difference;
+        self
+    }
+
+    /// Stroke of the rectangle marking the main map's viewport.
+    pub fn with_stroke(mut self, stroke: Stroke) -> Self {
+        self.stroke = stroke;
+        self
+    }
+
+    /// Position clicked in the overview during the last frame, if any. Typically passed to
+    /// [`MapMemory::center_at`] of the main map.
+    pub fn clicked(&self) -> Option<Position> {
+        self.clicked
+    }
+}
+
+impl<P: Projection + Clone + 'static> Plugin for &mut MiniMap<'_, P> {
+    fn run(self: Box<Self>, ui: &mut Ui, response: &Response, projector: &ScreenProjector) {
+        self.clicked = None;
+
+        let rect = self
+            .anchor
+            .align_size_within_rect(self.size, response.rect.shrink(8.));
+        let center = projector.unproject(response.rect.center());
+        let [north_west, _, south_east, _] = projector.viewport_corners();
+
+        let zoom = (projector.zoom() - self.zoom_difference).max(0.);
+        let Ok(mut memory) = MapMemory::new(center, zoom) else {
+            return;
+        };
+
+        let map = Map::new(self.projection.clone(), &mut memory, center)
+            .with_layer(&mut *self.tiles, 1.)
+            .with_background(ui.visuals().extreme_bg_color)
+            .zoom_gesture(false)
+            .panning(false)
+            .double_click_to_zoom(false)
+            .keyboard_gestures(false);
+
+        let stroke = self.stroke;
+        let clicked = ui
+            .scope_builder(UiBuilder::new().max_rect(rect), |ui| {
+                map.show(ui, |ui, response, projector, _| {
+                    let painter = ui.painter_at(response.rect);
+                    painter.rect_stroke(
+                        projector.project_bounds(north_west, south_east),
+                        0.,
+                        stroke,
+                        StrokeKind::Middle,
+                    );
+                    painter.rect_stroke(
+                        response.rect,
+                        0.,
+                        ui.visuals().window_stroke,
+                        StrokeKind::Inside,
+                    );
+
+                    response
+                        .clicked()
+                        .then(|| response.interact_pointer_pos())
+                        .flatten()
+                        .map(|pos| projector.unproject(pos))
+                })
+                .inner
+            })
+            .inner;
+
+        self.clicked = clicked;
+    }
+}
+
+impl<P: Projection + Clone + 'static> Plugin for MiniMap<'_, P> {
+    fn run(mut self: Box<Self>, ui: &mut Ui, response: &Response, projector: &ScreenProjector) {
+        Box::new(&mut *self).run(ui, response, projector);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{Event, Modifiers, PointerButton, Pos2, RawInput, Rect};
+    use walkers::{MercatorProjection, TileId, TilePiece, lon_lat, sources::Attribution};
+
+    struct NoTiles;
+
+    impl Tiles for NoTiles {
+        type Projection = MercatorProjection;
+
+        fn at(&mut self, _tile_id: TileId) -> Option<TilePiece> {
+            None
+        }
+
+        fn attribution(&self) -> Attribution {
+            Attribution {
+                text: "",
+                url: "",
+                logo_light: None,
+                logo_dark: None,
+            }
+        }
+
+        fn tile_size(&self) -> u32 {
+            256
+        }
+    }
+
+    #[test]
+    fn clicking_the_overview_reports_the_position() {
+        let ctx = egui::Context::default();
+        let my_position = lon_lat(17., 51.);
+        let mut memory = MapMemory::new(my_position, 12.).expect("valid zoom");
+        let mut overview_tiles = NoTiles;
+
+        // Center of the overview, which is in the top right corner.
+        let pos = Pos2::new(400. - 8. - 100., 8. + 75.);
+        let events = [
+            vec![Event::PointerMoved(pos)],
+            vec![Event::PointerButton {
+                pos,
+                button: PointerButton::Primary,
+                pressed: true,
+                modifiers: Modifiers::NONE,
+            }],
+            vec![Event::PointerButton {
+                pos,
+                button: PointerButton::Primary,
+                pressed: false,
+                modifiers: Modifiers::NONE,
+            }],
+        ];
+
+        let mut clicked = None;
+        for (frame, events) in events.into_iter().enumerate() {
+            let input = RawInput {
+                screen_rect: Some(Rect::from_min_size(Pos2::ZERO, Vec2::new(400., 300.))),
+                time: Some(frame as f64 / 60.),
+                events,
+                ..Default::default()
+            };
+
+            let mut minimap = MiniMap::new(MercatorProjection, &mut overview_tiles);
+            let _ = ctx.run_ui(input, |ui| {
+                ui.add(
+                    Map::new(MercatorProjection, &mut memory, my_position)
+                        .with_plugin(&mut minimap),
+                );
+            });
+            clicked = minimap.clicked();
+        }
+
+        let clicked = clicked.expect("overview was clicked");
+        assert!((clicked.x() - my_position.x()).abs() < 0.1, "{clicked:?}");
+        assert!((clicked.y() - my_position.y()).abs() < 0.1, "{clicked:?}");
+    }
+}
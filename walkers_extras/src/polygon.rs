@@ -0,0 +1,256 @@
+use egui::{Color32, Pos2, Rect, Response, Shape, Stroke, Ui, pos2};
+use lyon_path::geom::point;
+use walkers::{Plugin, Position, Projection, ScreenProjector, tessellate_polygon};
+
+use crate::polyline::{unwrap_longitudes, visible_runs};
+
+/// Latitude at which the Web Mercator projection ends.
+const MAX_MERCATOR_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// [`Plugin`] which draws a filled polygon with an outline, e.g. to highlight a region.
+#[derive(Clone)]
+pub struct Polygon {
+    /// Outer ring of the polygon.
+    pub exterior: Vec<Position>,
+
+    /// Holes in the polygon.
+    pub interiors: Vec<Vec<Position>>,
+
+    /// Fill color, typically a translucent one.
+    pub fill: Color32,
+
+    /// Outline of the polygon, including its holes.
+    pub stroke: Stroke,
+}
+
+impl Polygon {
+    pub fn new(exterior: Vec<Position>, fill: Color32, stroke: Stroke) -> Self {
+        Self {
+            exterior,
+            interiors: Vec::new(),
+            fill,
+            stroke,
+        }
+    }
+
+    /// Add a hole to the polygon.
+    pub fn with_interior(mut self, interior: Vec<Position>) -> Self {
+        self.interiors.push(interior);
+        self
+    }
+}
+
+impl Plugin for Polygon {
+    fn run(self: Box<Self>, ui: &mut Ui, _response: &Response, projector: &ScreenProjector) {
+        draw_polygon(
+            ui.painter(),
+            projector,
+            &self.exterior,
+            &self.interiors,
+            self.fill,
+            self.stroke,
+        );
+    }
+}
+
+/// Draw a polygon with holes, projecting all vertices with the `projector`.
+pub(crate) fn draw_polygon(
+    painter: &egui::Painter,
+    projector: &ScreenProjector,
+    exterior: &[Position],
+    interiors: &[Vec<Position>],
+    fill: Color32,
+    stroke: Stroke,
+) {
+    // Vertices lying far away from the viewport end up with huge screen coordinates, which
+    // become imprecise in `f32`. Clipping the rings keeps the fill mesh close to the screen.
+    let fill_rect = projector.clip_rect.expand(stroke.width + 1.);
+
+    let exterior = project_ring(exterior, projector);
+    let interiors: Vec<_> = interiors
+        .iter()
+        .map(|interior| project_ring(interior, projector))
+        .collect();
+
+    if fill != Color32::TRANSPARENT {
+        let clipped_exterior = lyon_ring(&clip_ring(&exterior, fill_rect));
+        let clipped_interiors: Vec<_> = interiors
+            .iter()
+            .map(|interior| lyon_ring(&clip_ring(interior, fill_rect)))
+            .filter(|interior| !interior.is_empty())
+            .collect();
+
+        if clipped_exterior.len() >= 3 {
+            match tessellate_polygon(&clipped_exterior, &clipped_interiors, fill) {
+                Ok(mesh) => {
+                    painter.add(mesh);
+                }
+                Err(err) => log::warn!("Could not tessellate polygon: {err}"),
+            }
+        }
+    }
+
+    if stroke.width > 0. {
+        for ring in std::iter::once(&exterior).chain(&interiors) {
+            let closed: Vec<_> = ring.iter().chain(ring.first()).copied().collect();
+            for run in visible_runs(&closed, fill_rect) {
+                painter.add(Shape::line(run, stroke));
+            }
+        }
+    }
+}
+
+/// Project a ring onto the screen. For the Mercator projection, latitudes are clamped to the
+/// range the projection is defined for, so that rings reaching the poles stay finite.
+fn project_ring<P: Projection + ?Sized>(
+    ring: &[Position],
+    projector: &ScreenProjector<'_, P>,
+) -> Vec<Pos2> {
+    let mercator = projector.projection.is_mercator();
+
+    unwrap_longitudes(ring, projector)
+        .into_iter()
+        .map(|position| {
+            if mercator {
+                Position::new(
+                    position.x(),
+                    position
+                        .y()
+                        .clamp(-MAX_MERCATOR_LATITUDE, MAX_MERCATOR_LATITUDE),
+                )
+            } else {
+                position
+            }
+        })
+        .map(|position| projector.project_without_wrapping(position))
+        .collect()
+}
+
+fn lyon_ring(ring: &[Pos2]) -> Vec<lyon_path::geom::Point<f32>> {
+    ring.iter().map(|p| point(p.x, p.y)).collect()
+}
+
+/// Clip a closed ring with a rectangle using the
+/// [Sutherland–Hodgman algorithm](https://en.wikipedia.org/wiki/Sutherland%E2%80%93Hodgman_algorithm).
+///
+/// The result of clipping a concave polygon might contain degenerate edges running along the
+/// rectangle, which is fine for filling, but not for drawing an outline.
+fn clip_ring(ring: &[Pos2], rect: Rect) -> Vec<Pos2> {
+    let mut output = ring.to_vec();
+
+    for edge in [
+        Edge::Left(rect.min.x),
+        Edge::Right(rect.max.x),
+        Edge::Top(rect.min.y),
+        Edge::Bottom(rect.max.y),
+    ] {
+        let input = std::mem::take(&mut output);
+        let Some(mut previous) = input.last().copied() else {
+            break;
+        };
+
+        for current in input {
+            match (edge.inside(current), edge.inside(previous)) {
+                (true, true) => output.push(current),
+                (true, false) => {
+                    output.push(edge.intersect(previous, current));
+                    output.push(current);
+                }
+                (false, true) => output.push(edge.intersect(previous, current)),
+                (false, false) => {}
+            }
+            previous = current;
+        }
+    }
+
+    output
+}
+
+/// Edge of the clipping rectangle.
+#[derive(Clone, Copy)]
+enum Edge {
+    Left(f32),
+    Right(f32),
+    Top(f32),
+    Bottom(f32),
+}
+
+impl Edge {
+    fn inside(self, p: Pos2) -> bool {
+        match self {
+            Edge::Left(x) => p.x >= x,
+            Edge::Right(x) => p.x <= x,
+            Edge::Top(y) => p.y >= y,
+            Edge::Bottom(y) => p.y <= y,
+        }
+    }
+
+    /// Point where the segment `a`-`b` crosses the edge.
+    fn intersect(self, a: Pos2, b: Pos2) -> Pos2 {
+        match self {
+            Edge::Left(x) | Edge::Right(x) => {
+                let t = (x - a.x) / (b.x - a.x);
+                pos2(x, a.y + t * (b.y - a.y))
+            }
+            Edge::Top(y) | Edge::Bottom(y) => {
+                let t = (y - a.y) / (b.y - a.y);
+                pos2(a.x + t * (b.x - a.x), y)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::Vec2;
+
+    #[test]
+    fn clipping_ring_with_rect() {
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.));
+
+        // Fully inside.
+        let ring = vec![pos2(10., 10.), pos2(90., 10.), pos2(50., 90.)];
+        assert_eq!(clip_ring(&ring, rect), ring);
+
+        // Much larger than the rect, so it becomes the rect itself.
+        let ring = vec![
+            pos2(-1e9, -1e9),
+            pos2(1e9, -1e9),
+            pos2(1e9, 1e9),
+            pos2(-1e9, 1e9),
+        ];
+        let clipped = clip_ring(&ring, rect);
+        assert_eq!(clipped.len(), 4);
+        assert!(
+            clipped
+                .iter()
+                .all(|p| rect.expand(0.01).contains(*p) && !rect.shrink(0.01).contains(*p))
+        );
+
+        // Fully outside.
+        let ring = vec![pos2(110., 10.), pos2(190., 10.), pos2(150., 90.)];
+        assert!(clip_ring(&ring, rect).is_empty());
+    }
+
+    #[test]
+    fn partially_visible_ring_is_cut_at_the_edge() {
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.));
+        let ring = vec![
+            pos2(50., 10.),
+            pos2(150., 10.),
+            pos2(150., 90.),
+            pos2(50., 90.),
+        ];
+
+        assert_eq!(
+            clip_ring(&ring, rect),
+            vec![
+                pos2(50., 10.),
+                pos2(100., 10.),
+                pos2(100., 90.),
+                pos2(50., 90.)
+            ]
+        );
+    }
+}
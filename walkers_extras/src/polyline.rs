@@ -0,0 +1,196 @@
+use egui::{Color32, Pos2, Rect, Response, Shape, Stroke, Ui};
+use walkers::{Plugin, Position, Projection, ScreenProjector};
+
+/// Width of a line drawn on the map.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineWidth {
+    /// Constant width in screen points, regardless of the zoom level.
+    Pixels(f32),
+
+    /// Width in meters on the ground, so the line gets thicker when zooming in.
+    Meters(f32),
+}
+
+impl LineWidth {
+    /// Width in screen points at the given position.
+    pub fn in_pixels(&self, position: Position, projector: &ScreenProjector) -> f32 {
+        match self {
+            LineWidth::Pixels(pixels) => *pixels,
+            LineWidth::Meters(meters) => meters * projector.scale_pixel_per_meter(position),
+        }
+    }
+}
+
+/// [`Plugin`] which draws a line connecting given positions, e.g. a GPS route.
+#[derive(Clone)]
+pub struct Polyline {
+    /// Ordered positions of the line's vertices.
+    pub points: Vec<Position>,
+
+    /// Width of the line.
+    pub width: LineWidth,
+
+    /// Color of the line.
+    pub color: Color32,
+}
+
+impl Polyline {
+    pub fn new(points: Vec<Position>, width: LineWidth, color: Color32) -> Self {
+        Self {
+            points,
+            width,
+            color,
+        }
+    }
+}
+
+impl Plugin for Polyline {
+    fn run(self: Box<Self>, ui: &mut Ui, _response: &Response, projector: &ScreenProjector) {
+        let width = self
+            .width
+            .in_pixels(projector.unproject(projector.clip_rect.center()), projector);
+
+        draw_polyline(
+            ui.painter(),
+            projector,
+            &self.points,
+            Stroke::new(width, self.color),
+        );
+    }
+}
+
+/// Draw a line connecting given positions, skipping segments which are not visible.
+pub(crate) fn draw_polyline(
+    painter: &egui::Painter,
+    projector: &ScreenProjector,
+    points: &[Position],
+    stroke: Stroke,
+) {
+    let projected: Vec<_> = unwrap_longitudes(points, projector)
+        .into_iter()
+        .map(|position| projector.project_without_wrapping(position))
+        .collect();
+
+    for run in visible_runs(&projected, projector.clip_rect.expand(stroke.width)) {
+        painter.add(Shape::line(run, stroke));
+    }
+}
+
+/// Shift longitudes so that consecutive points are never more than 180° apart. This way, lines
+/// crossing the antimeridian take the shorter way instead of going around the whole world. The
+/// first point is shifted to be the closest to the map's center, the same as
+/// [`ScreenProjector::project`] does.
+///
+/// Positions are returned unchanged if the projection is not a geographical one.
+pub(crate) fn unwrap_longitudes<P: Projection + ?Sized>(
+    points: &[Position],
+    projector: &ScreenProjector<'_, P>,
+) -> Vec<Position> {
+    if !projector.projection.is_geographic() {
+        return points.to_vec();
+    }
+
+    let center = projector.unproject(projector.clip_rect.center());
+    let mut unwrapped: Vec<Position> = Vec::with_capacity(points.len());
+
+    for point in points {
+        let reference = unwrapped.last().unwrap_or(&center);
+        let delta = point.x() - reference.x();
+        let turns = ((delta + 180.) / 360.).floor();
+        unwrapped.push(Position::new(point.x() - turns * 360., point.y()));
+    }
+
+    unwrapped
+}
+
+/// Split the line into runs of consecutive segments which are at least partially visible, so
+/// segments lying entirely outside of the `clip_rect` are not drawn at all.
+pub(crate) fn visible_runs(points: &[Pos2], clip_rect: Rect) -> Vec<Vec<Pos2>> {
+    let mut runs = Vec::new();
+    let mut current: Vec<Pos2> = Vec::new();
+
+    for segment in points.windows(2) {
+        if clip_rect.intersects(Rect::from_two_pos(segment[0], segment[1])) {
+            if current.is_empty() {
+                current.push(segment[0]);
+            }
+            current.push(segment[1]);
+        } else if !current.is_empty() {
+            runs.push(std::mem::take(&mut current));
+        }
+    }
+
+    if !current.is_empty() {
+        runs.push(current);
+    }
+
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{Vec2, pos2};
+    use walkers::{MapMemory, MercatorProjection, lon_lat};
+
+    #[test]
+    fn line_crossing_antimeridian_takes_the_shorter_way() {
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.));
+        let projector = ScreenProjector::new(
+            &MercatorProjection,
+            rect,
+            &MapMemory::default(),
+            lon_lat(0., 0.),
+        );
+
+        let unwrapped = unwrap_longitudes(
+            &[lon_lat(179., 10.), lon_lat(-179., 11.), lon_lat(-178., 12.)],
+            &projector,
+        );
+
+        assert_eq!(
+            unwrapped,
+            vec![lon_lat(179., 10.), lon_lat(181., 11.), lon_lat(182., 12.)]
+        );
+    }
+
+    #[test]
+    fn line_is_unwrapped_next_to_the_map_center() {
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.));
+        let projector = ScreenProjector::new(
+            &MercatorProjection,
+            rect,
+            &MapMemory::default(),
+            lon_lat(179.5, 0.),
+        );
+
+        let unwrapped = unwrap_longitudes(&[lon_lat(-179., 10.), lon_lat(-178., 11.)], &projector);
+
+        assert_eq!(unwrapped, vec![lon_lat(181., 10.), lon_lat(182., 11.)]);
+    }
+
+    #[test]
+    fn segments_outside_of_the_viewport_are_culled() {
+        let clip_rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.));
+
+        let runs = visible_runs(
+            &[
+                pos2(10., 10.),
+                pos2(50., 50.),
+                pos2(500., 50.),
+                pos2(500., 500.),
+                pos2(50., 90.),
+                pos2(90., 90.),
+            ],
+            clip_rect,
+        );
+
+        assert_eq!(
+            runs,
+            vec![
+                vec![pos2(10., 10.), pos2(50., 50.), pos2(500., 50.)],
+                vec![pos2(500., 500.), pos2(50., 90.), pos2(90., 90.)],
+            ]
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use egui::{Align2, Color32, FontId, Response, Stroke, StrokeKind, Ui, vec2};
+use walkers::{Plugin, ScreenProjector, visible_tiles};
+
+/// [`Plugin`] which outlines tiles covering the map and labels them with their zoom/x/y. Useful
+/// for debugging tile sources and projection issues.
+pub struct TileGrid {
+    tile_size: Option<u32>,
+    stroke: Stroke,
+}
+
+impl TileGrid {
+    /// Grid of tiles of the map's bottom layer.
+    pub fn new() -> Self {
+        Self {
+            tile_size: None,
+            stroke: Stroke::new(1., Color32::RED),
+        }
+    }
+
+    /// Show tiles of given size instead, which should match [`walkers::Tiles::tile_size`] of the
+    /// source being debugged.
+    pub fn with_tile_size(mut self, tile_size: u32) -> Self {
+        self.tile_size = Some(tile_size);
+        self
+    }
+
+    /// Stroke of tile outlines and color of labels. Default is a thin red line.
+    pub fn with_stroke(mut self, stroke: Stroke) -> Self {
+        self.stroke = stroke;
+        self
+    }
+}
+
+impl Default for TileGrid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugin for TileGrid {
+    fn run(self: Box<Self>, ui: &mut Ui, _response: &Response, projector: &ScreenProjector) {
+        let painter = ui.painter();
+        let map_center = projector.unproject(projector.clip_rect.center());
+
+        for (tile_id, rect) in visible_tiles(
+            projector.projection,
+            map_center,
+            projector.zoom(),
+            self.tile_size.unwrap_or(projector.tile_size),
+            projector.clip_rect,
+        ) {
+            painter.rect_stroke(rect, 0., self.stroke, StrokeKind::Inside);
+            painter.text(
+                rect.left_top() + vec2(4., 4.),
+                Align2::LEFT_TOP,
+                format!("{}/{}/{}", tile_id.zoom, tile_id.x, tile_id.y),
+                FontId::monospace(12.),
+                self.stroke.color,
+            );
+        }
+    }
+}